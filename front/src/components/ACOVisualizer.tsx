@@ -24,6 +24,7 @@ export default function ACOVisualizer() {
   const [alpha, setAlpha] = useState([1.0]);
   const [beta, setBeta] = useState([2.0]);
   const [animationSpeed, setAnimationSpeed] = useState([1.0]);
+  const [algorithm, setAlgorithm] = useState("as");
 
   // Don't auto-initialize, let user control initialization
 
@@ -167,7 +168,14 @@ export default function ACOVisualizer() {
       return;
     }
 
-    engine.initialize_colony(numAnts[0], maxGenerations[0], evaporationRate[0], alpha[0], beta[0]);
+    engine.set_algorithm(algorithm);
+    try {
+      engine.initialize_colony(numAnts[0], maxGenerations[0], evaporationRate[0], alpha[0], beta[0]);
+    } catch (err) {
+      // The problem settings do not fit the cities
+      alert(`${err}`);
+      return;
+    }
 
     engine.set_animation_speed(animationSpeed[0]);
     engine.start();
@@ -361,6 +369,23 @@ export default function ACOVisualizer() {
             <CardTitle>アルゴリズムパラメータ</CardTitle>
           </CardHeader>
           <CardContent className="space-y-4">
+            <div>
+              <Label htmlFor="algorithm">アルゴリズム</Label>
+              <select
+                id="algorithm"
+                value={algorithm}
+                onChange={(event) => setAlgorithm(event.target.value)}
+                className="mt-2 w-full rounded-md border border-border bg-background px-3 py-2 text-sm"
+                disabled={isRunning}
+              >
+                <option value="as">Ant System</option>
+                <option value="mmas">MAX-MIN Ant System</option>
+                <option value="acs">Ant Colony System</option>
+                <option value="elitist">Elitist Ant System</option>
+                <option value="rank">Rank-based Ant System</option>
+              </select>
+            </div>
+
             <div>
               <Label>アリの数: {numAnts[0]}</Label>
               <Slider
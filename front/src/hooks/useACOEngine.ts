@@ -1,9 +1,38 @@
 import { useEffect, useState } from "react";
 
+type Visit = { city: number; arrival: number; start: number; departure: number; lateness: number };
+
+// What the engine reports about the best solution, depending on the problem being solved
+type SolutionSummary =
+  | { schedules: Visit[][]; time_window_violations: number }
+  | {
+      objective: string;
+      total_distance: number;
+      longest_tour: number;
+      tours: { salesman: number; route: number[]; distance: number }[];
+    }
+  | { collected: number; length: number; skipped: number[] }
+  | { precedence_violations: [number, number][] }
+  | { assignment: number[] };
+
 interface ACOEngineInstance {
   add_city: (x: number, y: number) => number;
   clear_cities: () => void;
   get_city_count: () => number;
+  load_tsplib: (text: string) => number;
+  load_distance_matrix: (matrix: Float64Array, coordinates?: Float64Array) => number;
+  // x, y pairs, i.e. longitude, latitude for the "geo" and "haversine" metrics; throws on bad points
+  load_coordinates: (coordinates: Float64Array, metric: string) => number;
+  load_optimal_tour: (text: string) => number;
+  load_qaplib: (text: string) => number;
+  load_qap_solution: (text: string) => number;
+  // Multi-criteria runs need the "as" algorithm without update rule override, local search or polishing
+  add_cost_criterion: (name: string, matrix: Float64Array) => number;
+  clear_cost_criteria: () => void;
+  get_criterion_names: () => string[];
+  get_optimal_distance: () => number | undefined;
+  get_optimality_gap: () => number | undefined;
+  export_best_tour: (name: string) => string;
   initialize_canvas: (canvas: HTMLCanvasElement) => void;
   resize_canvas: (width: number, height: number) => void;
   initialize_colony: (
@@ -11,8 +40,33 @@ interface ACOEngineInstance {
     max_generations: number,
     evaporation_rate: number,
     alpha: number,
-    beta: number
-  ) => void;
+    beta: number,
+    seed?: number
+  ) => void; // throws if the problem settings do not fit the cities
+  set_algorithm: (name: string) => boolean;
+  get_algorithm: () => string;
+  set_update_strategy: (name: string) => boolean;
+  get_update_strategy: () => string;
+  get_update_strategy_names: () => string[];
+  set_local_search: (mode: string) => boolean;
+  set_local_search_operator: (name: string) => boolean;
+  set_polishing: (interval: number, on_complete: boolean) => void;
+  set_candidate_list_size: (size: number) => void;
+  // "geo" and "haversine" are refused unless the cities come from load_coordinates
+  set_distance_metric: (name: string) => boolean;
+  set_route_mode: (mode: string, start?: number, end?: number) => boolean;
+  set_vehicle_routing: (demands: Float64Array, depot: number, capacity: number) => boolean;
+  clear_vehicle_routing: () => void;
+  set_time_windows: (ready: Float64Array, due: Float64Array, service: Float64Array) => boolean;
+  set_urgency_weight: (weight: number) => void;
+  set_salesmen: (count: number, depot: number, objective: string) => boolean;
+  clear_salesmen: () => void;
+  set_precedence: (pairs: Uint32Array) => boolean;
+  clear_precedence: () => void;
+  set_prizes: (prizes: Float64Array) => boolean;
+  set_prize_goal: (goal: string, depot: number, budget?: number) => boolean;
+  clear_prize_goal: () => void;
+  polish_best_route: () => number;
   start: () => void;
   stop: () => void;
   run_iteration: () => boolean;
@@ -20,8 +74,21 @@ interface ACOEngineInstance {
   update_animation: (timestamp: number) => boolean;
   set_animation_speed: (speed: number) => void;
   get_best_distance: () => number;
+  get_local_search_gain: () => number;
+  get_polish_gain: () => number;
+  get_seed: () => number | undefined;
+  get_pheromone_matrix_flat: () => Float64Array;
+  get_min_pheromone: () => number;
+  get_max_pheromone: () => number;
   get_generation: () => number;
   get_best_route: () => number[];
+  get_best_routes: () => number[][];
+  get_time_window_violations: () => number;
+  get_solution_summary: () => SolutionSummary | null;
+  get_pareto_front: () => { route: number[]; costs: number[] }[];
+  select_pareto_tour: (index?: number) => boolean;
+  render_pareto_front: (canvas: HTMLCanvasElement, x: number, y: number) => void;
+  get_vehicle_count: () => number;
   is_complete: () => boolean;
   is_running: () => boolean;
 }
@@ -1,4 +1,5 @@
-use crate::geometry::city::City;
+use crate::aco::choice::ChoiceInfo;
+use crate::geometry::distance::DistanceMatrix;
 use rand::Rng;
 
 #[derive(Debug, Clone)]
@@ -7,6 +8,19 @@ pub struct Ant {
     visited_cities: Vec<bool>,
     route: Vec<usize>,
     total_distance: f64,
+    complete: bool,
+    // Demand collected since the vehicle last left the depot and the time it is ready to
+    // leave the current city (vehicle routing only)
+    load: f64,
+    time: f64,
+    scratch: Scratch,
+}
+
+// Buffers reused by select_next_city to avoid allocating on every step
+#[derive(Debug, Clone, Default)]
+struct Scratch {
+    options: Vec<usize>,
+    weights: Vec<f64>,
 }
 
 impl Ant {
@@ -19,64 +33,195 @@ impl Ant {
             visited_cities: visited,
             route: vec![start_city],
             total_distance: 0.0,
+            complete: false,
+            load: 0.0,
+            time: 0.0,
+            scratch: Scratch {
+                options: Vec::with_capacity(num_cities),
+                weights: Vec::with_capacity(num_cities),
+            },
         }
     }
 
-    pub fn select_next_city(&mut self, cities: &[City], pheromone_matrix: &[Vec<f64>], alpha: f64, beta: f64) -> Option<usize> {
-        let unvisited: Vec<usize> = (0..cities.len())
-            .filter(|&i| !self.visited_cities[i])
-            .collect();
+    // With probability q0 the ant greedily exploits the best edge (ACS pseudo-random
+    // proportional rule); otherwise it samples with the Ant System random-proportional rule.
+    // With a candidate list only its unvisited cities are considered; once all of them are
+    // visited the ant moves to the best remaining city.
+    pub fn select_next_city<R: Rng>(
+        &mut self,
+        choice_info: &ChoiceInfo,
+        q0: f64,
+        candidates: Option<&[usize]>,
+        rng: &mut R,
+    ) -> Option<usize> {
+        self.select_next_city_where(choice_info, q0, candidates, rng, |_, _| Some(1.0))
+    }
 
-        if unvisited.is_empty() {
-            return None;
-        }
+    // As select_next_city, for constrained problems: `factor` looks at the ant and returns None
+    // for cities that cannot be visited next and otherwise a multiplier for their weight
+    pub fn select_next_city_where<R: Rng>(
+        &mut self,
+        choice_info: &ChoiceInfo,
+        q0: f64,
+        candidates: Option<&[usize]>,
+        rng: &mut R,
+        factor: impl Fn(&Ant, usize) -> Option<f64>,
+    ) -> Option<usize> {
+        // The buffers are taken out so that `factor` can see the whole ant
+        let mut scratch = std::mem::take(&mut self.scratch);
+        let next = self.choose(&mut scratch, choice_info, q0, candidates, rng, |city| factor(self, city));
+        self.scratch = scratch;
+        next
+    }
 
-        let mut probabilities = Vec::new();
-        let mut total_prob = 0.0;
+    fn choose<R: Rng>(
+        &self,
+        scratch: &mut Scratch,
+        choice_info: &ChoiceInfo,
+        q0: f64,
+        candidates: Option<&[usize]>,
+        rng: &mut R,
+        factor: impl Fn(usize) -> Option<f64>,
+    ) -> Option<usize> {
+        let Scratch { options, weights } = scratch;
+        let current = self.current_city;
+        let num_cities = choice_info.size();
+        let weight = |city: usize| choice_info.get(current, city) * factor(city).unwrap_or(0.0);
+        let visited = &self.visited_cities;
+        let eligible = |city: usize| !visited[city] && factor(city).is_some();
 
-        for &city in &unvisited {
-            let distance = cities[self.current_city].distance_to(&cities[city]);
-            let pheromone = pheromone_matrix[self.current_city][city];
-            let probability = pheromone.powf(alpha) * (1.0 / distance).powf(beta);
-            probabilities.push(probability);
-            total_prob += probability;
+        options.clear();
+        match candidates {
+            Some(list) => options.extend(list.iter().copied().filter(|&city| eligible(city))),
+            None => options.extend((0..num_cities).filter(|&city| eligible(city))),
         }
 
+        if options.is_empty() {
+            return (0..num_cities)
+                .filter(|&city| eligible(city))
+                .max_by(|&a, &b| weight(a).total_cmp(&weight(b)));
+        }
+
+        weights.clear();
+        weights.extend(options.iter().map(|&city| weight(city)));
+        let total_prob: f64 = weights.iter().sum();
+
         if total_prob == 0.0 {
-            return Some(unvisited[0]);
+            return Some(options[0]);
+        }
+
+        if q0 > 0.0 && rng.gen::<f64>() < q0 {
+            let best = weights
+                .iter()
+                .enumerate()
+                .max_by(|a, b| a.1.total_cmp(b.1))
+                .map(|(i, _)| i)
+                .unwrap_or(0);
+            return Some(options[best]);
         }
 
-        let mut rng = rand::thread_rng();
         let random_value = rng.gen::<f64>() * total_prob;
         let mut cumulative_prob = 0.0;
 
-        for (i, &prob) in probabilities.iter().enumerate() {
+        for (i, &prob) in weights.iter().enumerate() {
             cumulative_prob += prob;
             if random_value <= cumulative_prob {
-                return Some(unvisited[i]);
+                return Some(options[i]);
             }
         }
 
-        Some(unvisited[0])
+        Some(options[0])
     }
 
-    pub fn move_to_city(&mut self, city: usize, cities: &[City]) {
+    pub fn move_to_city(&mut self, city: usize, distances: &DistanceMatrix) {
         if !self.visited_cities[city] {
-            let distance = cities[self.current_city].distance_to(&cities[city]);
-            self.total_distance += distance;
+            self.total_distance += distances.get(self.current_city, city);
             self.visited_cities[city] = true;
             self.route.push(city);
             self.current_city = city;
         }
     }
 
-    pub fn complete_tour(&mut self, cities: &[City]) {
+    // Adds a component without travelling, for problems where cost is not a sum of distances
+    pub fn visit(&mut self, component: usize) {
+        if !self.visited_cities[component] {
+            self.visited_cities[component] = true;
+            self.route.push(component);
+            self.current_city = component;
+        }
+    }
+
+    // Ends construction where the ant is
+    pub fn stop(&mut self) {
+        self.complete = true;
+    }
+
+    pub fn complete_tour(&mut self, distances: &DistanceMatrix) {
         if !self.route.is_empty() {
             let start_city = self.route[0];
-            let distance = cities[self.current_city].distance_to(&cities[start_city]);
-            self.total_distance += distance;
+            self.total_distance += distances.get(self.current_city, start_city);
             self.route.push(start_city);
         }
+        self.complete = true;
+    }
+
+    // Closes the current vehicle route at the depot and empties the vehicle
+    pub fn return_to_depot(&mut self, depot: usize, distances: &DistanceMatrix) {
+        self.total_distance += distances.get(self.current_city, depot);
+        self.route.push(depot);
+        self.current_city = depot;
+        self.load = 0.0;
+        self.time = 0.0;
+    }
+
+    pub fn add_load(&mut self, demand: f64) {
+        self.load += demand;
+    }
+
+    pub fn load(&self) -> f64 {
+        self.load
+    }
+
+    pub fn set_time(&mut self, time: f64) {
+        self.time = time;
+    }
+
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+
+    pub fn is_visited(&self, city: usize) -> bool {
+        self.visited_cities[city]
+    }
+
+    pub fn has_unvisited(&self) -> bool {
+        self.visited_cities.iter().any(|&visited| !visited)
+    }
+
+    // Keeps `city` out of the selection, e.g. a fixed end that may only be visited last
+    pub fn reserve_city(&mut self, city: usize) {
+        self.visited_cities[city] = true;
+    }
+
+    // Ends an open path instead of returning to the start, finishing at `end` if given
+    pub fn complete_path(&mut self, end: Option<usize>, distances: &DistanceMatrix) {
+        if let Some(end) = end {
+            self.total_distance += distances.get(self.current_city, end);
+            self.route.push(end);
+            self.current_city = end;
+        }
+        self.complete = true;
+    }
+
+    // Swaps in a tour visiting the same cities, e.g. after local search
+    pub fn replace_route(&mut self, route: Vec<usize>, total_distance: f64) {
+        self.current_city = route.last().copied().unwrap_or(self.current_city);
+        self.route = route;
+        self.total_distance = total_distance;
+    }
+
+    pub fn current_city(&self) -> usize {
+        self.current_city
     }
 
     pub fn route(&self) -> &Vec<usize> {
@@ -88,13 +233,16 @@ impl Ant {
     }
 
     pub fn is_tour_complete(&self) -> bool {
-        self.route.len() > 1 && self.route.first() == self.route.last()
+        self.complete
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::aco::pheromone::PheromoneMatrix;
+    use crate::geometry::city::City;
+    use rand::{rngs::SmallRng, SeedableRng};
 
     #[test]
     fn test_ant_creation() {
@@ -115,8 +263,9 @@ mod tests {
             City::new(1, 3.0, 4.0),
             City::new(2, 6.0, 8.0),
         ];
+        let distances = DistanceMatrix::from_cities(&cities);
         
-        ant.move_to_city(1, &cities);
+        ant.move_to_city(1, &distances);
         assert_eq!(ant.current_city, 1);
         assert_eq!(ant.route.len(), 2);
         assert_eq!(ant.total_distance, 5.0);
@@ -131,13 +280,107 @@ mod tests {
             City::new(1, 3.0, 4.0),
             City::new(2, 6.0, 8.0),
         ];
+        let distances = DistanceMatrix::from_cities(&cities);
         
-        ant.move_to_city(1, &cities);
-        ant.move_to_city(2, &cities);
-        ant.complete_tour(&cities);
+        ant.move_to_city(1, &distances);
+        ant.move_to_city(2, &distances);
+        ant.complete_tour(&distances);
         
         assert!(ant.is_tour_complete());
         assert_eq!(ant.route.len(), 4);
         assert_eq!(ant.route[0], ant.route[3]);
     }
-}
\ No newline at end of file
+
+    fn choice_info(cities: &[City]) -> ChoiceInfo {
+        let mut info = ChoiceInfo::new(&DistanceMatrix::from_cities(cities), 1.0, 2.0);
+        info.update(&PheromoneMatrix::new(cities.len(), 1.0));
+        info
+    }
+
+    #[test]
+    fn test_ant_select_next_city_greedy() {
+        let mut ant = Ant::new(0, 4);
+        let cities = vec![
+            City::new(0, 0.0, 0.0),
+            City::new(1, 10.0, 0.0),
+            City::new(2, 1.0, 0.0),
+            City::new(3, 5.0, 0.0),
+        ];
+        let choice_info = choice_info(&cities);
+
+        // q0 = 1.0 always exploits, so the nearest city must be chosen
+        assert_eq!(ant.select_next_city(&choice_info, 1.0, None, &mut SmallRng::seed_from_u64(1)), Some(2));
+    }
+
+    #[test]
+    fn test_ant_select_next_city_candidates() {
+        let mut ant = Ant::new(0, 4);
+        let cities = vec![
+            City::new(0, 0.0, 0.0),
+            City::new(1, 10.0, 0.0),
+            City::new(2, 1.0, 0.0),
+            City::new(3, 5.0, 0.0),
+        ];
+        let choice_info = choice_info(&cities);
+        let mut rng = SmallRng::seed_from_u64(1);
+
+        // Only city 1 is a candidate, so it is picked even though others are closer
+        assert_eq!(ant.select_next_city(&choice_info, 0.0, Some(&[1]), &mut rng), Some(1));
+
+        // Every candidate is visited: fall back to the best remaining city
+        ant.move_to_city(1, &DistanceMatrix::from_cities(&cities));
+        assert_eq!(ant.select_next_city(&choice_info, 0.0, Some(&[0]), &mut rng), Some(3));
+    }
+
+    #[test]
+    fn test_ant_complete_path_with_reserved_end() {
+        let cities = vec![
+            City::new(0, 0.0, 0.0),
+            City::new(1, 1.0, 0.0),
+            City::new(2, 5.0, 0.0),
+        ];
+        let distances = DistanceMatrix::from_cities(&cities);
+        let mut ant = Ant::new(0, 3);
+        ant.reserve_city(1);
+
+        // The nearest city is reserved, so the ant has to go to city 2 first
+        let next = ant.select_next_city(&choice_info(&cities), 1.0, None, &mut SmallRng::seed_from_u64(1));
+        assert_eq!(next, Some(2));
+        ant.move_to_city(2, &distances);
+        assert!(!ant.is_tour_complete());
+
+        ant.complete_path(Some(1), &distances);
+        assert!(ant.is_tour_complete());
+        assert_eq!(ant.route(), &vec![0, 2, 1]);
+        assert_eq!(ant.total_distance(), 9.0);
+    }
+
+    #[test]
+    fn test_ant_vehicle_routes() {
+        let cities = vec![
+            City::new(0, 0.0, 0.0),
+            City::new(1, 3.0, 0.0),
+            City::new(2, 0.0, 4.0),
+        ];
+        let distances = DistanceMatrix::from_cities(&cities);
+        let choice_info = choice_info(&cities);
+        let mut rng = SmallRng::seed_from_u64(1);
+        let mut ant = Ant::new(0, 3);
+
+        ant.move_to_city(1, &distances);
+        ant.add_load(7.0);
+        // City 2 does not fit into the remaining capacity, so nothing is feasible
+        assert_eq!(ant.select_next_city_where(&choice_info, 0.0, None, &mut rng, |_, city| (city != 2).then_some(1.0)), None);
+
+        ant.return_to_depot(0, &distances);
+        assert_eq!(ant.load(), 0.0);
+        assert!(ant.has_unvisited());
+        assert_eq!(ant.select_next_city_where(&choice_info, 0.0, None, &mut rng, |_, _| Some(1.0)), Some(2));
+        ant.move_to_city(2, &distances);
+        ant.return_to_depot(0, &distances);
+
+        assert!(!ant.has_unvisited());
+        assert_eq!(ant.route(), &vec![0, 1, 0, 2, 0]);
+        assert_eq!(ant.total_distance(), 14.0);
+    }
+}
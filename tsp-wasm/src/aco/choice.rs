@@ -0,0 +1,124 @@
+use crate::aco::pheromone::PheromoneMatrix;
+use crate::geometry::distance::DistanceMatrix;
+
+// Guards the heuristic against coincident cities
+const MIN_DISTANCE: f64 = 1e-10;
+
+// Combined attractiveness tau^alpha * eta^beta of every edge. eta^beta only depends on the
+// distances and is computed once; the product is refreshed once per generation (and per
+// edge for ACS local updates) instead of on every ant step.
+#[derive(Debug, Clone)]
+pub struct ChoiceInfo {
+    size: usize,
+    alpha: f64,
+    heuristic: Vec<f64>,
+    values: Vec<f64>,
+}
+
+impl ChoiceInfo {
+    pub fn new(distances: &DistanceMatrix, alpha: f64, beta: f64) -> Self {
+        Self::from_heuristic(distances.size(), alpha, beta, |i, j| inverse_distance(distances, i, j))
+    }
+
+    // For problems whose heuristic eta(i, j) is not the inverse distance
+    pub fn from_heuristic(size: usize, alpha: f64, beta: f64, eta: impl Fn(usize, usize) -> f64) -> Self {
+        let mut heuristic = vec![0.0; size * size];
+        for i in 0..size {
+            for j in 0..size {
+                if i != j {
+                    heuristic[i * size + j] = eta(i, j).powf(beta);
+                }
+            }
+        }
+
+        ChoiceInfo {
+            size,
+            alpha,
+            values: heuristic.clone(),
+            heuristic,
+        }
+    }
+
+    // Every edge equally attractive, for ants that weigh the edges themselves
+    pub fn uniform(size: usize) -> Self {
+        Self::from_heuristic(size, 1.0, 1.0, |_, _| 1.0)
+    }
+
+    pub fn update(&mut self, pheromone: &PheromoneMatrix) {
+        for (i, row) in pheromone.get_matrix().iter().enumerate() {
+            for (j, &tau) in row.iter().enumerate() {
+                let index = i * self.size + j;
+                self.values[index] = self.combine(tau, self.heuristic[index]);
+            }
+        }
+    }
+
+    pub fn update_edge(&mut self, i: usize, j: usize, pheromone: &PheromoneMatrix) {
+        for (from, to) in [(i, j), (j, i)] {
+            let index = from * self.size + to;
+            self.values[index] = self.combine(pheromone.get(from, to), self.heuristic[index]);
+        }
+    }
+
+    fn combine(&self, tau: f64, eta: f64) -> f64 {
+        // Skip powf for the common alpha = 1
+        if self.alpha == 1.0 {
+            tau * eta
+        } else {
+            tau.powf(self.alpha) * eta
+        }
+    }
+
+    pub fn get(&self, i: usize, j: usize) -> f64 {
+        self.values[i * self.size + j]
+    }
+
+    pub fn heuristic(&self, i: usize, j: usize) -> f64 {
+        self.heuristic[i * self.size + j]
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+}
+
+// The usual TSP heuristic eta = 1 / d
+pub fn inverse_distance(distances: &DistanceMatrix, i: usize, j: usize) -> f64 {
+    1.0 / distances.get(i, j).max(MIN_DISTANCE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::city::City;
+
+    fn distances() -> DistanceMatrix {
+        DistanceMatrix::from_cities(&[
+            City::new(0, 0.0, 0.0),
+            City::new(1, 2.0, 0.0),
+            City::new(2, 0.0, 4.0),
+        ])
+    }
+
+    #[test]
+    fn test_choice_info_heuristic() {
+        let info = ChoiceInfo::new(&distances(), 1.0, 2.0);
+        assert_eq!(info.size(), 3);
+        assert_eq!(info.heuristic(0, 1), 0.25);
+        assert_eq!(info.heuristic(2, 0), 1.0 / 16.0);
+        assert_eq!(info.heuristic(1, 1), 0.0);
+    }
+
+    #[test]
+    fn test_choice_info_update() {
+        let mut info = ChoiceInfo::new(&distances(), 2.0, 1.0);
+        let mut pheromone = PheromoneMatrix::new(3, 3.0);
+        info.update(&pheromone);
+        assert_eq!(info.get(0, 1), 9.0 * 0.5);
+
+        pheromone.set(0, 2, 2.0);
+        info.update_edge(0, 2, &pheromone);
+        assert_eq!(info.get(2, 0), 4.0 * 0.25);
+        assert_eq!(info.get(0, 1), 9.0 * 0.5);
+    }
+}
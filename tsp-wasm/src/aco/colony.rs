@@ -1,5 +1,51 @@
-use crate::aco::{ant::Ant, pheromone::PheromoneMatrix};
-use crate::geometry::city::City;
+use crate::aco::{
+    ant::Ant,
+    choice::ChoiceInfo,
+    local_search::{self, LocalSearchMode, LocalSearchOperator, PathSearchSpace},
+    pareto::{MultiObjective, ParetoTour},
+    pheromone::PheromoneMatrix,
+    problem::{Problem, Tsp},
+    update::{default_strategy, PheromoneUpdateStrategy, UpdateContext},
+    vrp,
+};
+use crate::geometry::{
+    city::City, distance::DistanceMatrix, layout::circle_layout, metric::DistanceMetric, neighbors::NeighborLists,
+    route::RouteMode,
+};
+use rand::{rngs::SmallRng, SeedableRng};
+use serde_json::Value;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AcoVariant {
+    AntSystem,
+    MaxMin,
+    AntColonySystem,
+    Elitist,
+    RankBased,
+}
+
+impl AcoVariant {
+    pub fn from_name(name: &str) -> Option<AcoVariant> {
+        match name.to_ascii_lowercase().as_str() {
+            "as" | "ant-system" => Some(AcoVariant::AntSystem),
+            "mmas" | "max-min" => Some(AcoVariant::MaxMin),
+            "acs" | "ant-colony-system" => Some(AcoVariant::AntColonySystem),
+            "elitist" | "eas" => Some(AcoVariant::Elitist),
+            "rank" | "rank-based" | "asrank" => Some(AcoVariant::RankBased),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            AcoVariant::AntSystem => "as",
+            AcoVariant::MaxMin => "mmas",
+            AcoVariant::AntColonySystem => "acs",
+            AcoVariant::Elitist => "elitist",
+            AcoVariant::RankBased => "rank",
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct ACOParameters {
@@ -9,6 +55,42 @@ pub struct ACOParameters {
     pub alpha: f64,
     pub beta: f64,
     pub initial_pheromone: f64,
+    pub variant: AcoVariant,
+    // MMAS: probability that the best tour is rebuilt once converged, used to derive tau_min
+    pub p_best: f64,
+    // MMAS: deposit with the global best every n-th generation instead of the iteration best (0 = never)
+    pub global_best_interval: usize,
+    // MMAS: generations without improvement before the trails are reinitialised (0 = never)
+    pub stagnation_limit: usize,
+    // MMAS: how far trails are pulled towards tau_max on reinitialisation (1.0 = full reset)
+    pub trail_smoothing: f64,
+    // ACS: probability of greedily taking the best edge instead of sampling
+    pub q0: f64,
+    // ACS: local evaporation applied to an edge as soon as an ant crosses it
+    pub local_evaporation: f64,
+    // Elitist AS: weight e of the extra deposit on the best-so-far tour
+    pub elitist_weight: f64,
+    // Rank-based AS: the best w-1 ants of a generation plus the best-so-far tour deposit
+    pub rank_size: usize,
+    // Restrict city selection to the k nearest cities (0 = consider every city)
+    pub candidate_list_size: usize,
+    pub local_search: LocalSearchMode,
+    pub local_search_operator: LocalSearchOperator,
+    // Length of the per-city neighbour lists scanned by local search
+    pub local_search_neighbors: usize,
+    // Run chained Lin-Kernighan on the best tour every n generations (0 = never)
+    pub polish_interval: usize,
+    pub polish_on_complete: bool,
+    // Double-bridge kicks per chained Lin-Kernighan run
+    pub polish_kicks: usize,
+    // Seed of the colony's random number generator (None = pick one from entropy)
+    pub seed: Option<u64>,
+    // How distances between cities are measured when no explicit matrix is given
+    pub distance_metric: DistanceMetric,
+    // Closed tours, or open paths with optional fixed start and end cities
+    pub route_mode: RouteMode,
+    // VRPTW: how strongly customers with little slack or waiting time are preferred (0 = ignore)
+    pub urgency_weight: f64,
 }
 
 impl Default for ACOParameters {
@@ -20,81 +102,497 @@ impl Default for ACOParameters {
             alpha: 1.0,
             beta: 2.0,
             initial_pheromone: 1.0,
+            variant: AcoVariant::AntSystem,
+            p_best: 0.05,
+            global_best_interval: 10,
+            stagnation_limit: 50,
+            trail_smoothing: 0.5,
+            q0: 0.9,
+            local_evaporation: 0.1,
+            elitist_weight: 5.0,
+            rank_size: 6,
+            candidate_list_size: 20,
+            local_search: LocalSearchMode::Off,
+            local_search_operator: LocalSearchOperator::TwoOpt,
+            local_search_neighbors: 20,
+            polish_interval: 0,
+            polish_on_complete: false,
+            polish_kicks: 50,
+            seed: None,
+            distance_metric: DistanceMetric::Euclidean,
+            route_mode: RouteMode::Closed,
+            urgency_weight: 1.0,
         }
     }
 }
 
+pub fn nearest_neighbour_distance(distances: &DistanceMatrix) -> f64 {
+    let size = distances.size();
+    if size < 2 {
+        return 0.0;
+    }
+
+    let mut visited = vec![false; size];
+    let mut current = 0;
+    let mut total = 0.0;
+    visited[0] = true;
+
+    for _ in 1..size {
+        let next = (0..size)
+            .filter(|&i| !visited[i])
+            .min_by(|&a, &b| distances.get(current, a).total_cmp(&distances.get(current, b)))
+            .unwrap();
+        total += distances.get(current, next);
+        visited[next] = true;
+        current = next;
+    }
+
+    total + distances.get(current, 0)
+}
+
 #[derive(Debug)]
 pub struct Colony {
+    update_strategy: Box<dyn PheromoneUpdateStrategy>,
     cities: Vec<City>,
+    distances: DistanceMatrix,
+    neighbors: NeighborLists,
+    path_space: Option<PathSearchSpace>,
+    // Drives the construction of solutions; a TSP through all cities unless set otherwise
+    problem: Box<dyn Problem>,
+    // Further cost criteria besides the distances; the colony then searches for the Pareto front
+    objectives: Option<MultiObjective>,
+    candidates: Option<NeighborLists>,
+    choice_info: ChoiceInfo,
     pheromone_matrix: PheromoneMatrix,
     parameters: ACOParameters,
     best_route: Option<Vec<usize>>,
     best_distance: f64,
+    best_violations: usize,
     generation: usize,
+    initial_trail: f64,
+    local_search_gain: f64,
+    polish_gain: f64,
+    seed: u64,
+    rng: SmallRng,
 }
 
 impl Colony {
     pub fn new(cities: Vec<City>, parameters: ACOParameters) -> Self {
-        let num_cities = cities.len();
-        let pheromone_matrix = PheromoneMatrix::new(num_cities, parameters.initial_pheromone);
+        let distances = DistanceMatrix::with_metric(&cities, parameters.distance_metric);
+        Self::with_distance_matrix(cities, distances, parameters)
+    }
+
+    // Builds a colony from a cost matrix alone; the cities are placed at `coordinates` when given,
+    // otherwise on a circle, and only serve for drawing
+    pub fn from_cost_matrix(distances: DistanceMatrix, coordinates: Option<&[(f64, f64)]>, parameters: ACOParameters) -> Self {
+        let points = match coordinates {
+            Some(points) => points.to_vec(),
+            None => circle_layout(distances.size()),
+        };
+        let cities = points
+            .iter()
+            .enumerate()
+            .map(|(i, &(x, y))| City::new(i as u32, x, y))
+            .collect();
+        Self::with_distance_matrix(cities, distances, parameters)
+    }
+
+    // Tours are measured with `distances`; the cities only provide coordinates for drawing
+    pub fn with_distance_matrix(cities: Vec<City>, distances: DistanceMatrix, parameters: ACOParameters) -> Self {
+        assert_eq!(cities.len(), distances.size(), "one city per distance matrix row");
+        assert!(parameters.route_mode.fits(cities.len()), "route endpoints must be cities");
+        let initial_trail = Self::initial_trail(&distances, &parameters);
+        let seed = parameters.seed.unwrap_or_else(rand::random);
+        let problem: Box<dyn Problem> = Box::new(Tsp::new(distances.clone(), parameters.route_mode));
         
         Colony {
+            update_strategy: default_strategy(&parameters),
+            neighbors: NeighborLists::new(&distances, parameters.local_search_neighbors),
+            path_space: PathSearchSpace::new(&distances, parameters.route_mode, parameters.local_search_neighbors),
+            objectives: None,
+            candidates: Self::candidate_lists(&distances, &parameters),
+            choice_info: Self::choice_info(problem.as_ref(), &parameters),
+            // Asymmetric costs need directional trails
+            pheromone_matrix: if problem.is_symmetric() {
+                PheromoneMatrix::new(cities.len(), initial_trail)
+            } else {
+                PheromoneMatrix::asymmetric(cities.len(), initial_trail)
+            },
+            problem,
             cities,
-            pheromone_matrix,
+            distances,
             parameters,
             best_route: None,
             best_distance: f64::INFINITY,
+            best_violations: usize::MAX,
             generation: 0,
+            initial_trail,
+            local_search_gain: 0.0,
+            polish_gain: 0.0,
+            seed,
+            rng: SmallRng::seed_from_u64(seed),
+        }
+    }
+
+    // Solves a problem that is not a tour through `cities`, e.g. an assignment problem: there is
+    // one city per solution component, used only for drawing, and no distance between them
+    pub fn with_problem(cities: Vec<City>, problem: Box<dyn Problem>, parameters: ACOParameters) -> Self {
+        assert_eq!(cities.len(), problem.size(), "one city per solution component");
+        let parameters = ACOParameters { candidate_list_size: 0, route_mode: RouteMode::Closed, ..parameters };
+        let distances = DistanceMatrix::from_fn(cities.len(), |_, _| 0.0);
+        let mut colony = Self::with_distance_matrix(cities, distances, parameters);
+        colony.set_problem(problem);
+        colony
+    }
+
+    fn initial_trail(distances: &DistanceMatrix, parameters: &ACOParameters) -> f64 {
+        let num_cities = distances.size();
+        match parameters.variant {
+            // ACS uses tau0 = 1 / (n * L_nn) both as initial trail and as local update target;
+            // without distances there is no tour length to derive it from
+            AcoVariant::AntColonySystem => match nearest_neighbour_distance(distances) {
+                length if length > 0.0 => 1.0 / (num_cities as f64 * length),
+                _ => parameters.initial_pheromone,
+            },
+            _ => parameters.initial_pheromone,
         }
     }
 
+    fn choice_info(problem: &dyn Problem, parameters: &ACOParameters) -> ChoiceInfo {
+        ChoiceInfo::from_heuristic(problem.size(), parameters.alpha, parameters.beta, |i, j| problem.heuristic(i, j))
+    }
+
+    fn candidate_lists(distances: &DistanceMatrix, parameters: &ACOParameters) -> Option<NeighborLists> {
+        (parameters.candidate_list_size > 0)
+            .then(|| NeighborLists::new(distances, parameters.candidate_list_size))
+    }
+
+    // Replaces the cities, rebuilding every distance-derived matrix and restarting the run; the
+    // colony goes back to a TSP through all of them
+    pub fn set_cities(&mut self, cities: Vec<City>) {
+        self.distances = DistanceMatrix::with_metric(&cities, self.parameters.distance_metric);
+        self.neighbors = NeighborLists::new(&self.distances, self.parameters.local_search_neighbors);
+        if !self.parameters.route_mode.fits(cities.len()) {
+            self.parameters.route_mode = RouteMode::Closed;
+        }
+        self.path_space = PathSearchSpace::new(
+            &self.distances,
+            self.parameters.route_mode,
+            self.parameters.local_search_neighbors,
+        );
+        self.candidates = Self::candidate_lists(&self.distances, &self.parameters);
+        self.initial_trail = Self::initial_trail(&self.distances, &self.parameters);
+        self.cities = cities;
+        self.set_problem(Box::new(Tsp::new(self.distances.clone(), self.parameters.route_mode)));
+    }
+
+    // Solves `problem` (vehicle routing, several salesmen, ...) instead and restarts the run;
+    // false if it is not over the colony's cities. Further criteria are dropped, their trails
+    // were laid out for the previous problem
+    pub fn set_problem(&mut self, problem: Box<dyn Problem>) -> bool {
+        if problem.size() != self.cities.len() {
+            return false;
+        }
+        self.choice_info = Self::choice_info(problem.as_ref(), &self.parameters);
+        self.problem = problem;
+        self.objectives = None;
+        self.restart();
+        true
+    }
+
+    // Weighs tours by further cost criteria besides the distances, one matrix per criterion, and
+    // restarts the run; the colony then keeps the non-dominated tours instead of a single best
+    // one. An empty list goes back to distance alone; false if a matrix does not match the cities
+    // or unless the colony runs plain Ant System (see `supports_criteria`)
+    pub fn set_criteria(&mut self, criteria: Vec<DistanceMatrix>) -> bool {
+        if criteria.is_empty() {
+            self.objectives = None;
+            self.restart();
+            return true;
+        }
+        if !self.supports_criteria() {
+            return false;
+        }
+        let all = std::iter::once(self.distances.clone()).chain(criteria).collect();
+        let Some(objectives) = MultiObjective::new(all, self.problem.is_symmetric(), self.initial_trail, &self.parameters)
+        else {
+            return false;
+        };
+        self.objectives = Some(objectives);
+        self.restart();
+        true
+    }
+
+    // With several criteria the archived tours deposit on one trail per criterion in place of
+    // the variant's update, which leaves Ant System as the only variant that keeps its meaning.
+    // Local search and polishing only shorten tours, pulling every ant to the distance end of
+    // the front
+    pub fn supports_criteria(&self) -> bool {
+        self.parameters.variant == AcoVariant::AntSystem
+            && self.update_strategy.name() == "as"
+            && self.parameters.local_search == LocalSearchMode::Off
+            && self.parameters.polish_interval == 0
+            && !self.parameters.polish_on_complete
+    }
+
+    fn restart(&mut self) {
+        if let Some(objectives) = &mut self.objectives {
+            objectives.reset(self.initial_trail);
+        }
+        self.pheromone_matrix = if self.problem.is_symmetric() {
+            PheromoneMatrix::new(self.cities.len(), self.initial_trail)
+        } else {
+            PheromoneMatrix::asymmetric(self.cities.len(), self.initial_trail)
+        };
+        self.best_route = None;
+        self.best_distance = f64::INFINITY;
+        self.best_violations = usize::MAX;
+        self.generation = 0;
+        self.local_search_gain = 0.0;
+        self.polish_gain = 0.0;
+        self.rng = SmallRng::seed_from_u64(self.seed);
+    }
+
     pub fn run_iteration(&mut self) -> bool {
         if self.generation >= self.parameters.max_generations {
             return false;
         }
 
-        let mut ants = Vec::new();
-        
-        for i in 0..self.parameters.num_ants {
-            let start_city = i % self.cities.len();
-            let mut ant = Ant::new(start_city, self.cities.len());
-            
-            while !ant.is_tour_complete() {
-                if let Some(next_city) = ant.select_next_city(
-                    &self.cities,
-                    self.pheromone_matrix.get_matrix(),
-                    self.parameters.alpha,
-                    self.parameters.beta,
-                ) {
-                    ant.move_to_city(next_city, &self.cities);
-                } else {
-                    ant.complete_tour(&self.cities);
-                }
+        let num_cities = self.cities.len();
+        let q0 = match self.parameters.variant {
+            AcoVariant::AntColonySystem => self.parameters.q0,
+            _ => 0.0,
+        };
+
+        let mut ants: Vec<Ant> = (0..self.parameters.num_ants).map(|i| self.problem.new_ant(i)).collect();
+        self.choice_info.update(&self.pheromone_matrix);
+        // With several criteria every ant weighs them differently
+        let weights: Vec<Vec<f64>> = match &mut self.objectives {
+            Some(objectives) => {
+                objectives.refresh();
+                ants.iter().map(|_| objectives.random_weights(&mut self.rng)).collect()
             }
-            
-            if !ant.is_tour_complete() {
-                ant.complete_tour(&self.cities);
+            None => Vec::new(),
+        };
+
+        // Ants advance in lockstep so ACS local updates are seen by the whole generation
+        let mut building = true;
+        while building {
+            building = false;
+            for (k, ant) in ants.iter_mut().enumerate() {
+                if ant.is_tour_complete() {
+                    continue;
+                }
+                building = true;
+
+                let from = ant.current_city();
+                self.advance_ant(ant, q0, weights.get(k).map(Vec::as_slice));
+
+                if self.parameters.variant == AcoVariant::AntColonySystem {
+                    let to = ant.current_city();
+                    self.pheromone_matrix.local_update(
+                        from,
+                        to,
+                        self.parameters.local_evaporation,
+                        self.initial_trail,
+                    );
+                    self.choice_info.update_edge(from, to, &self.pheromone_matrix);
+                }
             }
-            
-            ants.push(ant);
         }
 
-        self.pheromone_matrix.evaporate(self.parameters.evaporation_rate);
+        for ant in &mut ants {
+            self.problem.finish(ant);
+        }
+
+        self.local_search_gain = self.apply_local_search(&mut ants);
 
+        // Time-window violations outweigh distance when comparing solutions
+        let previous_best = self.best_distance;
         for ant in &ants {
-            if ant.total_distance() < self.best_distance {
+            let violations = self.problem.violations(ant.route());
+            if (violations, ant.total_distance()) < (self.best_violations, self.best_distance) {
+                self.best_violations = violations;
                 self.best_distance = ant.total_distance();
                 self.best_route = Some(ant.route().clone());
             }
-            
-            self.pheromone_matrix.deposit(ant.route(), ant.total_distance());
+        }
+
+        let context = UpdateContext {
+            ants: &ants,
+            best_route: self.best_route.as_deref(),
+            best_distance: self.best_distance,
+            improved: self.best_distance < previous_best,
+            generation: self.generation,
+            num_cities,
+        };
+        match &mut self.objectives {
+            Some(objectives) => objectives.update(&ants, self.parameters.evaporation_rate),
+            None => self.update_strategy.update(&context, &mut self.pheromone_matrix),
         }
 
         self.generation += 1;
+
+        let interval = self.parameters.polish_interval;
+        if (interval > 0 && self.generation.is_multiple_of(interval))
+            || (self.parameters.polish_on_complete && self.is_complete())
+        {
+            self.polish_best_route();
+        }
         true
     }
 
+    // Moves the ant one step, as far as the problem allows; with several criteria the ant follows
+    // their trails by its `weights` instead of the colony's own trail
+    fn advance_ant(&mut self, ant: &mut Ant, q0: f64, weights: Option<&[f64]>) {
+        let candidates = self.candidates.as_ref().map(|lists| lists.neighbors(ant.current_city()));
+        let problem = self.problem.as_ref();
+        let next = match (&self.objectives, weights) {
+            (Some(objectives), Some(weights)) => {
+                let uniform = objectives.uniform_choice_info();
+                ant.select_next_city_where(uniform, q0, candidates, &mut self.rng, |ant, component| {
+                    let attractiveness = objectives.attractiveness(ant.current_city(), component, weights);
+                    problem.weight(ant, component).map(|weight| weight * attractiveness)
+                })
+            }
+            _ => ant.select_next_city_where(&self.choice_info, q0, candidates, &mut self.rng, |ant, component| {
+                problem.weight(ant, component)
+            }),
+        };
+        problem.step(ant, next);
+    }
+
+    // Polishes the best-so-far tour with chained Lin-Kernighan; returns the distance saved
+    pub fn polish_best_route(&mut self) -> f64 {
+        if !self.supports_local_search() {
+            return 0.0;
+        }
+        let Some(mut route) = self.best_route.clone() else {
+            return 0.0;
+        };
+
+        let depot = self.depot();
+        let kicks = self.parameters.polish_kicks;
+        let rng = &mut self.rng;
+        let polish = |tour: &mut Vec<usize>, distances: &DistanceMatrix, neighbors: &NeighborLists| {
+            local_search::chained_lin_kernighan(tour, distances, neighbors, kicks, rng)
+        };
+        let gain = Self::improve_route(
+            &mut route,
+            depot,
+            self.path_space.as_ref(),
+            &self.distances,
+            &self.neighbors,
+            self.parameters.local_search_neighbors,
+            polish,
+        );
+        // Tours the polishing could not shorten stay as they were
+        if gain <= 0.0 {
+            return 0.0;
+        }
+        self.best_distance = self.route_cost(&route);
+        self.polish_gain += gain;
+        self.best_route = Some(route);
+        gain
+    }
+
+    // Total distance removed from the best tour by polishing so far
+    pub fn polish_gain(&self) -> f64 {
+        self.polish_gain
+    }
+
+    fn apply_local_search(&self, ants: &mut [Ant]) -> f64 {
+        let targets: Vec<usize> = match self.parameters.local_search {
+            LocalSearchMode::Off => Vec::new(),
+            LocalSearchMode::AllAnts => (0..ants.len()).collect(),
+            LocalSearchMode::IterationBest => ants
+                .iter()
+                .enumerate()
+                .min_by(|a, b| a.1.total_distance().total_cmp(&b.1.total_distance()))
+                .map(|(i, _)| vec![i])
+                .unwrap_or_default(),
+        };
+
+        let mut total_gain = 0.0;
+        for i in targets {
+            let mut route = ants[i].route().clone();
+            let operator = self.parameters.local_search_operator;
+            let search = |tour: &mut Vec<usize>, distances: &DistanceMatrix, neighbors: &NeighborLists| {
+                local_search::improve(tour, operator, distances, neighbors)
+            };
+            // Problems the tour moves would break bring their own search, if any
+            let gain = if self.supports_local_search() {
+                Self::improve_route(
+                    &mut route,
+                    self.depot(),
+                    self.path_space.as_ref(),
+                    &self.distances,
+                    &self.neighbors,
+                    self.parameters.local_search_neighbors,
+                    search,
+                )
+            } else {
+                self.problem.local_search(&mut route)
+            };
+            if gain > 0.0 {
+                let cost = self.route_cost(&route);
+                ants[i].replace_route(route, cost);
+                total_gain += gain;
+            }
+        }
+        total_gain
+    }
+
+    // Runs a tour operator on each depot round trip, on the path or on the whole tour
+    fn improve_route(
+        route: &mut Vec<usize>,
+        depot: Option<usize>,
+        path_space: Option<&PathSearchSpace>,
+        distances: &DistanceMatrix,
+        neighbors: &NeighborLists,
+        num_neighbors: usize,
+        mut search: impl FnMut(&mut Vec<usize>, &DistanceMatrix, &NeighborLists) -> f64,
+    ) -> f64 {
+        match (depot, path_space) {
+            (Some(depot), _) => vrp::improve_each_route(route, depot, distances, num_neighbors, search),
+            (None, Some(space)) => space.improve(route, search),
+            (None, None) => search(route, distances, neighbors),
+        }
+    }
+
+    fn route_cost(&self, route: &[usize]) -> f64 {
+        self.problem.cost(route)
+    }
+
+    // The shared start and end of all routes when vehicles, several salesmen or prize-collecting
+    // tours are used
+    pub fn depot(&self) -> Option<usize> {
+        self.problem.depot()
+    }
+
+    fn supports_local_search(&self) -> bool {
+        self.problem.supports_local_search()
+    }
+
+    // Distance removed by local search from the tours of the last generation
+    pub fn local_search_gain(&self) -> f64 {
+        self.local_search_gain
+    }
+
+    // Replaces the pheromone update rule chosen from `ACOParameters::variant`; false, keeping
+    // the current rule, if further criteria are set and the rule is not Ant System's
+    pub fn set_update_strategy(&mut self, strategy: Box<dyn PheromoneUpdateStrategy>) -> bool {
+        if self.objectives.is_some() && strategy.name() != "as" {
+            return false;
+        }
+        self.update_strategy = strategy;
+        true
+    }
+
+    pub fn update_strategy(&self) -> &dyn PheromoneUpdateStrategy {
+        self.update_strategy.as_ref()
+    }
+
     pub fn best_route(&self) -> Option<&Vec<usize>> {
         self.best_route.as_ref()
     }
@@ -103,17 +601,90 @@ impl Colony {
         self.best_distance
     }
 
+    // Length of the best solution's routes, which differs from its cost for prize tours
+    pub fn best_length(&self) -> f64 {
+        self.best_route.as_ref().map_or(f64::INFINITY, |route| self.problem.length(route))
+    }
+
     pub fn generation(&self) -> usize {
         self.generation
     }
 
+    // Seed the run was started with; passing it back in `ACOParameters::seed` replays the run
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
     pub fn cities(&self) -> &Vec<City> {
         &self.cities
     }
 
+    // How far apart the cities are, measured in the colony's metric or given as a cost matrix
+    pub fn distances(&self) -> &DistanceMatrix {
+        &self.distances
+    }
+
+    // False for asymmetric (ATSP) instances, where trails are directional and local search is skipped
+    pub fn is_symmetric(&self) -> bool {
+        self.pheromone_matrix.is_symmetric()
+    }
+
+    pub fn problem(&self) -> &dyn Problem {
+        self.problem.as_ref()
+    }
+
+    // The best solution split into vehicle routes or salesman tours; a TSP tour counts as a single route
+    pub fn best_vehicle_routes(&self) -> Vec<Vec<usize>> {
+        match (&self.best_route, self.depot()) {
+            (Some(route), Some(depot)) => vrp::split_at_depot(route, depot),
+            (Some(route), None) => vec![route.clone()],
+            (None, _) => Vec::new(),
+        }
+    }
+
+    // What the problem reports about the best solution, e.g. vehicle schedules; null without one
+    pub fn best_summary(&self) -> Value {
+        self.best_route.as_ref().map_or(Value::Null, |route| self.problem.summary(route))
+    }
+
+    // Constraints the best solution breaks, e.g. customers served after their due time
+    pub fn best_violations(&self) -> usize {
+        self.best_route.as_ref().map_or(0, |route| self.problem.violations(route))
+    }
+
+    pub fn variant(&self) -> AcoVariant {
+        self.parameters.variant
+    }
+
+    pub fn route_mode(&self) -> RouteMode {
+        self.parameters.route_mode
+    }
+
     pub fn is_complete(&self) -> bool {
         self.generation >= self.parameters.max_generations
     }
+
+    // With several criteria, the trail of the distances
+    pub fn pheromone_matrix(&self) -> &PheromoneMatrix {
+        match &self.objectives {
+            Some(objectives) => objectives.trail(0),
+            None => &self.pheromone_matrix,
+        }
+    }
+
+    pub fn objectives(&self) -> Option<&MultiObjective> {
+        self.objectives.as_ref()
+    }
+
+    // The non-dominated tours found so far with their cost for every criterion, distance first;
+    // empty unless further criteria are set
+    pub fn pareto_front(&self) -> Vec<ParetoTour> {
+        self.objectives.as_ref().map_or_else(Vec::new, |objectives| objectives.archive().tours())
+    }
+
+    pub fn pheromone_limits(&self) -> Option<(f64, f64)> {
+        self.update_strategy.pheromone_limits()
+    }
 }
 
 #[cfg(test)]
@@ -154,9 +725,11 @@ mod tests {
             City::new(1, 3.0, 4.0),
             City::new(2, 6.0, 8.0),
         ];
-        let mut params = ACOParameters::default();
-        params.num_ants = 5;
-        params.max_generations = 2;
+        let params = ACOParameters {
+            num_ants: 5,
+            max_generations: 2,
+            ..Default::default()
+        };
         
         let mut colony = Colony::new(cities, params);
         
@@ -171,4 +744,397 @@ mod tests {
         assert!(!colony.run_iteration());
         assert!(colony.is_complete());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_variant_from_name() {
+        assert_eq!(AcoVariant::from_name("mmas"), Some(AcoVariant::MaxMin));
+        assert_eq!(AcoVariant::from_name("AS"), Some(AcoVariant::AntSystem));
+        assert_eq!(AcoVariant::from_name("unknown"), None);
+        assert_eq!(AcoVariant::from_name(AcoVariant::RankBased.name()), Some(AcoVariant::RankBased));
+    }
+
+    #[test]
+    fn test_max_min_keeps_trails_within_limits() {
+        let cities: Vec<City> = (0..8)
+            .map(|i| {
+                let angle = i as f64 * std::f64::consts::TAU / 8.0;
+                City::new(i, 100.0 * angle.cos(), 100.0 * angle.sin())
+            })
+            .collect();
+        let params = ACOParameters {
+            num_ants: 8,
+            max_generations: 20,
+            variant: AcoVariant::MaxMin,
+            stagnation_limit: 5,
+            ..Default::default()
+        };
+
+        let mut colony = Colony::new(cities, params);
+        while colony.run_iteration() {}
+
+        let (tau_min, tau_max) = colony.pheromone_limits().unwrap();
+        for row in colony.pheromone_matrix.get_matrix() {
+            for &value in row {
+                assert!(value >= tau_min - 1e-12 && value <= tau_max + 1e-12);
+            }
+        }
+        assert!(colony.best_route().is_some());
+    }
+
+    #[test]
+    fn test_nearest_neighbour_distance() {
+        let cities = vec![
+            City::new(0, 0.0, 0.0),
+            City::new(1, 3.0, 0.0),
+            City::new(2, 3.0, 4.0),
+        ];
+        assert_eq!(nearest_neighbour_distance(&DistanceMatrix::from_cities(&cities)), 12.0);
+    }
+
+    #[test]
+    fn test_ant_colony_system_run() {
+        let cities = vec![
+            City::new(0, 0.0, 0.0),
+            City::new(1, 10.0, 0.0),
+            City::new(2, 10.0, 10.0),
+            City::new(3, 0.0, 10.0),
+        ];
+        let params = ACOParameters {
+            num_ants: 4,
+            max_generations: 10,
+            variant: AcoVariant::AntColonySystem,
+            ..Default::default()
+        };
+
+        let mut colony = Colony::new(cities, params);
+        let tau0 = colony.pheromone_matrix.get(0, 1);
+        assert!((tau0 - 1.0 / (4.0 * 40.0)).abs() < 1e-12);
+
+        while colony.run_iteration() {}
+        assert_eq!(colony.best_distance(), 40.0);
+        assert_eq!(colony.best_route().unwrap().len(), 5);
+    }
+
+    fn square_cities() -> Vec<City> {
+        vec![
+            City::new(0, 0.0, 0.0),
+            City::new(1, 10.0, 0.0),
+            City::new(2, 10.0, 10.0),
+            City::new(3, 0.0, 10.0),
+        ]
+    }
+
+    #[test]
+    fn test_elitist_reinforces_best_route() {
+        let params = ACOParameters {
+            num_ants: 1,
+            max_generations: 1,
+            evaporation_rate: 0.5,
+            variant: AcoVariant::Elitist,
+            elitist_weight: 2.0,
+            ..Default::default()
+        };
+        let mut colony = Colony::new(square_cities(), params);
+        colony.run_iteration();
+
+        // The only ant is also the best-so-far: 1 + e deposits of 1/L on top of the evaporated trail
+        let route = colony.best_route().unwrap().clone();
+        let expected = 0.5 + 3.0 / colony.best_distance();
+        assert!((colony.pheromone_matrix.get(route[0], route[1]) - expected).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_rank_based_run() {
+        let params = ACOParameters {
+            num_ants: 8,
+            max_generations: 5,
+            variant: AcoVariant::RankBased,
+            rank_size: 3,
+            ..Default::default()
+        };
+        let mut colony = Colony::new(square_cities(), params);
+        while colony.run_iteration() {}
+        assert_eq!(colony.best_distance(), 40.0);
+    }
+
+    #[test]
+    fn test_set_update_strategy() {
+        use crate::aco::update::HyperCubeUpdate;
+
+        let params = ACOParameters {
+            num_ants: 4,
+            max_generations: 3,
+            ..Default::default()
+        };
+        let mut colony = Colony::new(square_cities(), params);
+        assert_eq!(colony.update_strategy().name(), "as");
+
+        colony.set_update_strategy(Box::new(HyperCubeUpdate { evaporation_rate: 0.1 }));
+        while colony.run_iteration() {}
+        assert_eq!(colony.update_strategy().name(), "hyper-cube");
+        assert_eq!(colony.best_distance(), 40.0);
+    }
+
+    #[test]
+    fn test_local_search_reports_gain() {
+        let cities: Vec<City> = (0..30)
+            .map(|i| {
+                let angle = i as f64 * std::f64::consts::TAU / 30.0;
+                City::new(i, 100.0 * angle.cos(), 100.0 * angle.sin())
+            })
+            .collect();
+        let params = ACOParameters {
+            num_ants: 5,
+            max_generations: 1,
+            beta: 0.0,
+            local_search: LocalSearchMode::AllAnts,
+            ..Default::default()
+        };
+        let mut colony = Colony::new(cities, params);
+        colony.run_iteration();
+
+        // Without the distance heuristic the random tours are full of crossings
+        assert!(colony.local_search_gain() > 0.0);
+        let optimum = 30.0 * 2.0 * 100.0 * (std::f64::consts::PI / 30.0).sin();
+        assert!((colony.best_distance() - optimum).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_polish_on_complete() {
+        let cities: Vec<City> = (0..40)
+            .map(|i| City::new(i, ((i * 7919) % 1000) as f64, ((i * 104729 + 13) % 997) as f64))
+            .collect();
+        let params = ACOParameters {
+            num_ants: 2,
+            max_generations: 2,
+            alpha: 0.0,
+            beta: 0.0,
+            polish_on_complete: true,
+            polish_kicks: 5,
+            ..Default::default()
+        };
+        let mut colony = Colony::new(cities, params);
+        colony.run_iteration();
+        let unpolished = colony.best_distance();
+        assert_eq!(colony.polish_gain(), 0.0);
+
+        colony.run_iteration();
+        assert!(colony.is_complete());
+        assert!(colony.polish_gain() > 0.0);
+        assert!(colony.best_distance() < unpolished);
+
+        let route = colony.best_route().unwrap();
+        assert!((colony.distances.route_length(route) - colony.best_distance()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_candidate_lists_build_valid_tours() {
+        let cities: Vec<City> = (0..60)
+            .map(|i| City::new(i, ((i * 7919) % 1000) as f64, ((i * 104729 + 13) % 997) as f64))
+            .collect();
+        let params = ACOParameters {
+            num_ants: 10,
+            max_generations: 3,
+            candidate_list_size: 5,
+            ..Default::default()
+        };
+        let mut colony = Colony::new(cities, params);
+        while colony.run_iteration() {}
+
+        let route = colony.best_route().unwrap();
+        let mut visited = route[..60].to_vec();
+        visited.sort();
+        assert_eq!(visited, (0..60).collect::<Vec<_>>());
+        assert_eq!(route.first(), route.last());
+    }
+
+    #[test]
+    fn test_set_cities_restarts_run() {
+        let params = ACOParameters {
+            num_ants: 4,
+            max_generations: 5,
+            variant: AcoVariant::AntColonySystem,
+            ..Default::default()
+        };
+        let mut colony = Colony::new(square_cities(), params);
+        while colony.run_iteration() {}
+        assert_eq!(colony.best_distance(), 40.0);
+
+        let mut cities = square_cities();
+        cities.push(City::new(4, 5.0, 20.0));
+        colony.set_cities(cities);
+        assert_eq!(colony.generation(), 0);
+        assert!(colony.best_route().is_none());
+        assert_eq!(colony.choice_info.size(), 5);
+        assert!((colony.pheromone_matrix.get(0, 1) - colony.initial_trail).abs() < 1e-12);
+
+        while colony.run_iteration() {}
+        assert_eq!(colony.best_route().unwrap().len(), 6);
+    }
+
+    #[test]
+    fn test_seed_reproduces_run() {
+        let cities: Vec<City> = (0..25)
+            .map(|i| City::new(i, ((i * 7919) % 1000) as f64, ((i * 104729 + 13) % 997) as f64))
+            .collect();
+        let params = ACOParameters {
+            num_ants: 6,
+            max_generations: 8,
+            polish_interval: 4,
+            polish_kicks: 5,
+            seed: Some(42),
+            ..Default::default()
+        };
+
+        let history = |params: ACOParameters| {
+            let mut colony = Colony::new(cities.clone(), params);
+            let mut distances = Vec::new();
+            while colony.run_iteration() {
+                distances.push(colony.best_distance());
+            }
+            (distances, colony.best_route().unwrap().clone())
+        };
+
+        assert_eq!(history(params.clone()), history(params.clone()));
+
+        let colony = Colony::new(cities.clone(), ACOParameters { seed: None, ..params });
+        let replay = ACOParameters { seed: Some(colony.seed()), ..Default::default() };
+        assert_eq!(Colony::new(cities.clone(), replay).seed(), colony.seed());
+    }
+
+    #[test]
+    fn test_with_distance_matrix() {
+        // Only the crossing tour 0-2-1-3 is cheap, geometry would suggest the perimeter
+        let cheap = [(0, 2), (1, 2), (1, 3), (0, 3)];
+        let distances = DistanceMatrix::from_fn(4, |i, j| {
+            if cheap.contains(&(i.min(j), i.max(j))) { 1.0 } else { 100.0 }
+        });
+        let params = ACOParameters {
+            num_ants: 4,
+            max_generations: 5,
+            seed: Some(1),
+            ..Default::default()
+        };
+        let mut colony = Colony::with_distance_matrix(square_cities(), distances, params);
+        while colony.run_iteration() {}
+        assert_eq!(colony.best_distance(), 4.0);
+    }
+
+    #[test]
+    fn test_distance_metric_parameter() {
+        let params = ACOParameters {
+            num_ants: 4,
+            max_generations: 3,
+            distance_metric: DistanceMetric::Manhattan,
+            ..Default::default()
+        };
+        let mut cities = square_cities();
+        cities.push(City::new(4, 5.0, 5.0));
+        let mut colony = Colony::new(cities, params);
+        while colony.run_iteration() {}
+
+        // The centre detour costs 10 + 10 - 10 = 10 extra under Manhattan distances
+        assert_eq!(colony.best_distance(), 50.0);
+    }
+
+    #[test]
+    fn test_asymmetric_instance() {
+        // Going round 0 -> 1 -> 2 -> 3 costs 1 per edge, the opposite direction 10
+        let distances = DistanceMatrix::from_fn(4, |i, j| {
+            if (i + 1) % 4 == j {
+                1.0
+            } else if (j + 1) % 4 == i {
+                10.0
+            } else {
+                20.0
+            }
+        });
+        let params = ACOParameters {
+            num_ants: 4,
+            max_generations: 10,
+            local_search: LocalSearchMode::AllAnts,
+            polish_on_complete: true,
+            seed: Some(3),
+            ..Default::default()
+        };
+        let mut colony = Colony::with_distance_matrix(square_cities(), distances, params);
+        assert!(!colony.is_symmetric());
+        while colony.run_iteration() {}
+
+        assert_eq!(colony.best_distance(), 4.0);
+        let route = colony.best_route().unwrap();
+        let start = route[0];
+        assert_eq!(route[1], (start + 1) % 4);
+        assert!(colony.pheromone_matrix.get(start, route[1]) > colony.pheromone_matrix.get(route[1], start));
+        assert_eq!(colony.polish_gain(), 0.0);
+    }
+
+    #[test]
+    fn test_from_cost_matrix() {
+        let distances = DistanceMatrix::from_flat(vec![
+            0.0, 2.0, 9.0, 3.0,
+            2.0, 0.0, 4.0, 9.0,
+            9.0, 4.0, 0.0, 5.0,
+            3.0, 9.0, 5.0, 0.0,
+        ])
+        .unwrap();
+        let params = ACOParameters {
+            num_ants: 4,
+            max_generations: 5,
+            seed: Some(5),
+            ..Default::default()
+        };
+
+        let colony = Colony::from_cost_matrix(distances.clone(), None, params.clone());
+        assert_eq!(colony.cities().len(), 4);
+
+        let map = [(13.4, 52.5), (2.35, 48.86), (-0.13, 51.5), (12.5, 41.9)];
+        let mut colony = Colony::from_cost_matrix(distances, Some(&map), params);
+        assert_eq!(colony.cities()[2].x(), -0.13);
+        while colony.run_iteration() {}
+        assert_eq!(colony.best_distance(), 14.0);
+    }
+
+    fn line_cities() -> Vec<City> {
+        (0..6).map(|i| City::new(i, 10.0 * i as f64, 0.0)).collect()
+    }
+
+    #[test]
+    fn test_open_path() {
+        let params = ACOParameters {
+            num_ants: 6,
+            max_generations: 10,
+            route_mode: RouteMode::Open,
+            seed: Some(9),
+            ..Default::default()
+        };
+        let mut colony = Colony::new(line_cities(), params);
+        while colony.run_iteration() {}
+
+        let route = colony.best_route().unwrap();
+        assert_eq!(route.len(), 6);
+        assert_eq!(colony.best_distance(), 50.0);
+    }
+
+    #[test]
+    fn test_fixed_start_end_with_local_search() {
+        let params = ACOParameters {
+            num_ants: 4,
+            max_generations: 5,
+            beta: 0.0,
+            route_mode: RouteMode::FixedStartEnd { start: 2, end: 0 },
+            local_search: LocalSearchMode::AllAnts,
+            local_search_operator: LocalSearchOperator::TwoOptOrOpt,
+            polish_on_complete: true,
+            seed: Some(9),
+            ..Default::default()
+        };
+        let mut colony = Colony::new(line_cities(), params);
+        while colony.run_iteration() {}
+
+        let route = colony.best_route().unwrap();
+        assert_eq!((route[0], route[5]), (2, 0));
+        assert_eq!(colony.best_distance(), 80.0);
+        assert_eq!(colony.distances.route_length(route), 80.0);
+    }
+}
@@ -0,0 +1,266 @@
+use super::{ActiveCities, Tour, EPSILON};
+use crate::geometry::{distance::DistanceMatrix, neighbors::NeighborLists};
+use rand::Rng;
+
+const MAX_DEPTH: usize = 50;
+
+// Lin-Kernighan improvement built from sequential 2-opt flips (the array-based LK of
+// Johnson & McGeoch). Starting from edge (t1, t2) the chain keeps breaking the closing edge
+// at t1 and picks the next city from the neighbour list of the free end; the prefix of the
+// chain with the best closing gain is kept. Full breadth is only used on the first level.
+// Returns the distance saved.
+pub fn lin_kernighan(route: &mut Vec<usize>, distances: &DistanceMatrix, neighbors: &NeighborLists) -> f64 {
+    if route.len() < 6 {
+        return 0.0;
+    }
+
+    let start = route[0];
+    let mut tour = Tour::from_route(route);
+    let mut active = ActiveCities::new(&tour);
+
+    let mut total_gain = 0.0;
+    while let Some(t1) = active.pop() {
+        for t2 in [tour.next(t1), tour.prev(t1)] {
+            if let Some((gain, touched)) = improve_from(&mut tour, t1, t2, distances, neighbors) {
+                total_gain += gain;
+                for city in touched {
+                    active.activate(city);
+                }
+                break;
+            }
+        }
+    }
+
+    if total_gain > 0.0 {
+        *route = tour.into_route(start);
+    }
+    total_gain
+}
+
+// Chained LK (Applegate et al.): repeatedly perturbs the tour with a random double-bridge
+// kick, re-optimises it and keeps the result when it is shorter
+pub fn chained_lin_kernighan<R: Rng>(
+    route: &mut Vec<usize>,
+    distances: &DistanceMatrix,
+    neighbors: &NeighborLists,
+    kicks: usize,
+    rng: &mut R,
+) -> f64 {
+    let initial = distances.route_length(route);
+    lin_kernighan(route, distances, neighbors);
+
+    let mut current = distances.route_length(route);
+    if route.len() > 8 {
+        for _ in 0..kicks {
+            let mut candidate = double_bridge(route, rng);
+            lin_kernighan(&mut candidate, distances, neighbors);
+            let length = distances.route_length(&candidate);
+            if length < current - EPSILON {
+                *route = candidate;
+                current = length;
+            }
+        }
+    }
+
+    initial - current
+}
+
+// Splits the tour A B C D at three random points and reconnects it as A C B D
+fn double_bridge<R: Rng>(route: &[usize], rng: &mut R) -> Vec<usize> {
+    let n = route.len() - 1;
+    let mut cuts = [rng.gen_range(1..n), rng.gen_range(1..n), rng.gen_range(1..n)];
+    cuts.sort_unstable();
+    let [p1, mut p2, mut p3] = cuts;
+    if p2 == p1 {
+        p2 = (p1 + 1).min(n - 1);
+    }
+    if p3 <= p2 {
+        p3 = (p2 + 1).min(n);
+    }
+
+    let mut kicked = Vec::with_capacity(route.len());
+    kicked.extend_from_slice(&route[..p1]);
+    kicked.extend_from_slice(&route[p2..p3]);
+    kicked.extend_from_slice(&route[p1..p2]);
+    kicked.extend_from_slice(&route[p3..]);
+    kicked
+}
+
+// The city t4 that has to be dropped when (t2, t3) is added so the result is still a tour
+fn partner(tour: &Tour, t1: usize, t2: usize, t3: usize) -> Option<usize> {
+    let t4 = if tour.next(t1) == t2 { tour.prev(t3) } else { tour.next(t3) };
+    if t3 == t1 || t4 == t2 {
+        None
+    } else {
+        Some(t4)
+    }
+}
+
+fn same_edge(edge: (usize, usize), a: usize, b: usize) -> bool {
+    edge == (a, b) || edge == (b, a)
+}
+
+fn improve_from(
+    tour: &mut Tour,
+    t1: usize,
+    t2: usize,
+    distances: &DistanceMatrix,
+    neighbors: &NeighborLists,
+) -> Option<(f64, Vec<usize>)> {
+    let dist = |i: usize, j: usize| distances.get(i, j);
+    let g0 = dist(t1, t2);
+
+    for &t3 in neighbors.neighbors(t2) {
+        let g1 = g0 - dist(t2, t3);
+        if g1 <= EPSILON {
+            break;
+        }
+        let Some(t4) = partner(tour, t1, t2, t3) else {
+            continue;
+        };
+
+        // Each flip is stored as (t2, t3, t4) of its step; t1 never changes
+        let mut flips = vec![(t2, t3, t4)];
+        let mut added = vec![(t2, t3)];
+        let mut removed = vec![(t1, t2), (t3, t4)];
+        tour.two_opt_move(t1, t2, t4, t3);
+
+        let mut gain = g1 + dist(t3, t4);
+        let mut best_gain = gain - dist(t4, t1);
+        let mut best_len = 1;
+        let mut free = t4;
+
+        while flips.len() < MAX_DEPTH {
+            let mut choice: Option<(usize, usize, f64)> = None;
+            for &c in neighbors.neighbors(free) {
+                let g = gain - dist(free, c);
+                if g <= EPSILON {
+                    break;
+                }
+                let Some(d) = partner(tour, t1, free, c) else {
+                    continue;
+                };
+                if removed.iter().any(|&e| same_edge(e, free, c)) || added.iter().any(|&e| same_edge(e, c, d)) {
+                    continue;
+                }
+                let score = dist(c, d) - dist(free, c);
+                if choice.is_none_or(|(_, _, best)| score > best) {
+                    choice = Some((c, d, score));
+                }
+            }
+            let Some((c, d, _)) = choice else {
+                break;
+            };
+
+            tour.two_opt_move(t1, free, d, c);
+            flips.push((free, c, d));
+            added.push((free, c));
+            removed.push((c, d));
+
+            gain += dist(c, d) - dist(free, c);
+            let closing = gain - dist(d, t1);
+            if closing > best_gain {
+                best_gain = closing;
+                best_len = flips.len();
+            }
+            free = d;
+        }
+
+        let keep = if best_gain > EPSILON { best_len } else { 0 };
+        for &(b, c, d) in flips[keep..].iter().rev() {
+            tour.two_opt_move(t1, d, b, c);
+        }
+
+        if keep > 0 {
+            let mut touched = vec![t1];
+            for &(b, c, d) in &flips[..keep] {
+                touched.extend([b, c, d]);
+            }
+            return Some((best_gain, touched));
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aco::local_search::two_opt;
+    use crate::geometry::city::City;
+    use rand::{rngs::SmallRng, SeedableRng};
+
+    fn random_cities(n: usize) -> Vec<City> {
+        // Deterministic pseudo-random points so the test does not depend on the RNG
+        (0..n)
+            .map(|i| {
+                let x = ((i * 7919) % 1000) as f64;
+                let y = ((i * 104729 + 13) % 997) as f64;
+                City::new(i as u32, x, y)
+            })
+            .collect()
+    }
+
+    fn is_permutation(route: &[usize], n: usize) -> bool {
+        let mut visited = route[..n].to_vec();
+        visited.sort();
+        route.first() == route.last() && visited == (0..n).collect::<Vec<_>>()
+    }
+
+    #[test]
+    fn test_lin_kernighan_gain_matches_length() {
+        let cities = random_cities(60);
+        let distances = DistanceMatrix::from_cities(&cities);
+        let neighbors = NeighborLists::new(&distances, 8);
+
+        let mut route: Vec<usize> = (0..60).chain(std::iter::once(0)).collect();
+        let before = distances.route_length(&route);
+        let gain = lin_kernighan(&mut route, &distances, &neighbors);
+
+        assert!(is_permutation(&route, 60));
+        assert_eq!(route[0], 0);
+        assert!(gain > 0.0);
+        assert!((before - gain - distances.route_length(&route)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_lin_kernighan_at_least_as_good_as_two_opt() {
+        let cities = random_cities(80);
+        let distances = DistanceMatrix::from_cities(&cities);
+        let neighbors = NeighborLists::new(&distances, 10);
+
+        let mut route: Vec<usize> = (0..80).chain(std::iter::once(0)).collect();
+        two_opt(&mut route, &distances, &neighbors);
+        let two_opt_length = distances.route_length(&route);
+
+        lin_kernighan(&mut route, &distances, &neighbors);
+        assert!(distances.route_length(&route) <= two_opt_length + 1e-9);
+    }
+
+    #[test]
+    fn test_chained_lin_kernighan_never_worse() {
+        let cities = random_cities(40);
+        let distances = DistanceMatrix::from_cities(&cities);
+        let neighbors = NeighborLists::new(&distances, 8);
+        let mut rng = SmallRng::seed_from_u64(7);
+
+        let mut route: Vec<usize> = (0..40).chain(std::iter::once(0)).collect();
+        let mut plain = route.clone();
+        lin_kernighan(&mut plain, &distances, &neighbors);
+
+        let before = distances.route_length(&route);
+        let gain = chained_lin_kernighan(&mut route, &distances, &neighbors, 20, &mut rng);
+
+        assert!(is_permutation(&route, 40));
+        assert!((before - gain - distances.route_length(&route)).abs() < 1e-6);
+        assert!(distances.route_length(&route) <= distances.route_length(&plain) + 1e-9);
+    }
+
+    #[test]
+    fn test_double_bridge_keeps_cities() {
+        let route: Vec<usize> = (0..12).chain(std::iter::once(0)).collect();
+        let kicked = double_bridge(&route, &mut SmallRng::seed_from_u64(7));
+        assert_eq!(kicked[0], 0);
+        assert!(is_permutation(&kicked, 12));
+    }
+}
@@ -0,0 +1,317 @@
+pub mod lin_kernighan;
+pub mod or_opt;
+pub mod path;
+pub mod three_opt;
+pub mod two_opt;
+
+pub use lin_kernighan::{chained_lin_kernighan, lin_kernighan};
+pub use or_opt::or_opt;
+pub use path::PathSearchSpace;
+pub use three_opt::three_opt;
+pub use two_opt::two_opt;
+
+use crate::geometry::{distance::DistanceMatrix, neighbors::NeighborLists};
+use std::collections::VecDeque;
+
+const EPSILON: f64 = 1e-10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LocalSearchMode {
+    Off,
+    AllAnts,
+    IterationBest,
+}
+
+impl LocalSearchMode {
+    pub fn from_name(name: &str) -> Option<LocalSearchMode> {
+        match name.to_ascii_lowercase().as_str() {
+            "off" | "none" => Some(LocalSearchMode::Off),
+            "all" | "all-ants" => Some(LocalSearchMode::AllAnts),
+            "best" | "iteration-best" => Some(LocalSearchMode::IterationBest),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LocalSearchOperator {
+    TwoOpt,
+    OrOpt,
+    TwoOptOrOpt,
+    ThreeOpt,
+}
+
+impl LocalSearchOperator {
+    pub fn from_name(name: &str) -> Option<LocalSearchOperator> {
+        match name.to_ascii_lowercase().as_str() {
+            "2-opt" | "two-opt" => Some(LocalSearchOperator::TwoOpt),
+            "or-opt" => Some(LocalSearchOperator::OrOpt),
+            "2-opt+or-opt" | "2.5-opt" => Some(LocalSearchOperator::TwoOptOrOpt),
+            "3-opt" | "three-opt" => Some(LocalSearchOperator::ThreeOpt),
+            _ => None,
+        }
+    }
+}
+
+// Runs `operator` on a closed route until it reaches a local optimum; returns the distance saved.
+// 3-opt alternates with 2-opt, whose moves it does not try itself
+pub fn improve(
+    route: &mut Vec<usize>,
+    operator: LocalSearchOperator,
+    distances: &DistanceMatrix,
+    neighbors: &NeighborLists,
+) -> f64 {
+    match operator {
+        LocalSearchOperator::TwoOpt => two_opt(route, distances, neighbors),
+        LocalSearchOperator::OrOpt => or_opt(route, distances, neighbors),
+        LocalSearchOperator::ThreeOpt => {
+            until_no_gain(|| two_opt(route, distances, neighbors) + three_opt(route, distances, neighbors))
+        }
+        LocalSearchOperator::TwoOptOrOpt => {
+            until_no_gain(|| two_opt(route, distances, neighbors) + or_opt(route, distances, neighbors))
+        }
+    }
+}
+
+// Repeats a round of operators until it saves nothing more; returns the total saved
+fn until_no_gain(mut round: impl FnMut() -> f64) -> f64 {
+    let mut total = 0.0;
+    loop {
+        let gain = round();
+        if gain <= EPSILON {
+            return total;
+        }
+        total += gain;
+    }
+}
+
+// Work queue of cities whose don't-look bit is off
+pub(crate) struct ActiveCities {
+    queue: VecDeque<usize>,
+    dont_look: Vec<bool>,
+}
+
+impl ActiveCities {
+    pub(crate) fn new(tour: &Tour) -> Self {
+        let size = tour.position.len();
+        let mut active = ActiveCities {
+            queue: VecDeque::with_capacity(tour.len()),
+            dont_look: vec![true; size],
+        };
+        for &city in tour.cities() {
+            active.activate(city);
+        }
+        active
+    }
+
+    pub(crate) fn pop(&mut self) -> Option<usize> {
+        let city = self.queue.pop_front()?;
+        self.dont_look[city] = true;
+        Some(city)
+    }
+
+    pub(crate) fn activate(&mut self, city: usize) {
+        if self.dont_look[city] {
+            self.dont_look[city] = false;
+            self.queue.push_back(city);
+        }
+    }
+}
+
+// Array representation of a closed tour with a city -> position index, so operators
+// can look up neighbours in O(1) and reverse segments in place
+#[derive(Debug, Clone)]
+pub(crate) struct Tour {
+    order: Vec<usize>,
+    position: Vec<usize>,
+}
+
+impl Tour {
+    // Accepts routes with or without the closing city repeated at the end
+    pub(crate) fn from_route(route: &[usize]) -> Self {
+        let mut order = route.to_vec();
+        if order.len() > 1 && order.first() == order.last() {
+            order.pop();
+        }
+        let mut position = vec![0; order.iter().max().map_or(0, |&m| m + 1)];
+        for (i, &city) in order.iter().enumerate() {
+            position[city] = i;
+        }
+        Tour { order, position }
+    }
+
+    // Rotated so the route keeps starting at `start`, and closed again
+    pub(crate) fn into_route(self, start: usize) -> Vec<usize> {
+        let n = self.order.len();
+        let offset = self.position[start];
+        let mut route: Vec<usize> = (0..n).map(|i| self.order[(offset + i) % n]).collect();
+        route.push(start);
+        route
+    }
+
+    pub(crate) fn cities(&self) -> &[usize] {
+        &self.order
+    }
+
+    // Number of steps along `next` from `origin` to `city`
+    pub(crate) fn offset(&self, origin: usize, city: usize) -> usize {
+        let n = self.order.len();
+        (self.position[city] + n - self.position[origin]) % n
+    }
+
+    // Cities from `from` to `to` inclusive, following `next`
+    pub(crate) fn path(&self, from: usize, to: usize) -> Vec<usize> {
+        let n = self.order.len();
+        let start = self.position[from];
+        let len = self.offset(from, to) + 1;
+        (0..len).map(|i| self.order[(start + i) % n]).collect()
+    }
+
+    // 2-opt move removing edges (a, b) and (c, d) and adding (a, c) and (b, d). The removed
+    // edges must point the same way, i.e. b and d are both successors or both predecessors
+    pub(crate) fn two_opt_move(&mut self, a: usize, b: usize, c: usize, d: usize) {
+        if self.next(a) == b {
+            self.reverse_path(b, c);
+        } else {
+            self.reverse_path(a, d);
+        }
+    }
+
+    pub(crate) fn set_order(&mut self, order: Vec<usize>) {
+        for (i, &city) in order.iter().enumerate() {
+            self.position[city] = i;
+        }
+        self.order = order;
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    pub(crate) fn next(&self, city: usize) -> usize {
+        self.order[(self.position[city] + 1) % self.order.len()]
+    }
+
+    pub(crate) fn prev(&self, city: usize) -> usize {
+        let n = self.order.len();
+        self.order[(self.position[city] + n - 1) % n]
+    }
+
+    // Reverses the path from -> ... -> to (following `next`). Reversing the complementary
+    // path yields the same cycle, so the shorter of the two is flipped
+    pub(crate) fn reverse_path(&mut self, from: usize, to: usize) {
+        let n = self.order.len();
+        let mut i = self.position[from];
+        let mut j = self.position[to];
+        let mut len = (j + n - i) % n + 1;
+        if 2 * len > n {
+            let (ni, nj) = ((j + 1) % n, (i + n - 1) % n);
+            i = ni;
+            j = nj;
+            len = n - len;
+        }
+
+        for _ in 0..len / 2 {
+            self.order.swap(i, j);
+            self.position[self.order[i]] = i;
+            self.position[self.order[j]] = j;
+            i = (i + 1) % n;
+            j = (j + n - 1) % n;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::city::City;
+
+    #[test]
+    fn test_tour_round_trip() {
+        let tour = Tour::from_route(&[2, 0, 3, 1, 2]);
+        assert_eq!(tour.len(), 4);
+        assert_eq!(tour.next(1), 2);
+        assert_eq!(tour.prev(2), 1);
+        assert_eq!(tour.into_route(2), vec![2, 0, 3, 1, 2]);
+    }
+
+    #[test]
+    fn test_tour_reverse_path() {
+        let mut tour = Tour::from_route(&[0, 1, 2, 3, 4, 5, 0]);
+        tour.reverse_path(1, 3);
+        assert_eq!(tour.into_route(0), vec![0, 3, 2, 1, 4, 5, 0]);
+
+        // Long paths flip the complement instead: 0-1-2-3-4-5 is the same cycle as 0-5-4-3-2-1
+        let mut tour = Tour::from_route(&[0, 1, 2, 3, 4, 5, 0]);
+        tour.reverse_path(1, 5);
+        assert_eq!(tour.into_route(0), vec![0, 1, 2, 3, 4, 5, 0]);
+    }
+
+    #[test]
+    fn test_operator_from_name() {
+        assert_eq!(LocalSearchOperator::from_name("3-opt"), Some(LocalSearchOperator::ThreeOpt));
+        assert_eq!(LocalSearchOperator::from_name("Or-Opt"), Some(LocalSearchOperator::OrOpt));
+        assert_eq!(LocalSearchOperator::from_name("4-opt"), None);
+    }
+
+    #[test]
+    fn test_tour_path_and_offset() {
+        let tour = Tour::from_route(&[0, 1, 2, 3, 4, 0]);
+        assert_eq!(tour.offset(3, 1), 3);
+        assert_eq!(tour.path(3, 1), vec![3, 4, 0, 1]);
+    }
+
+    #[test]
+    fn test_tour_two_opt_move_either_orientation() {
+        let mut tour = Tour::from_route(&[0, 1, 2, 3, 4, 5, 0]);
+        tour.two_opt_move(0, 1, 3, 4);
+        assert_eq!(tour.into_route(0), vec![0, 3, 2, 1, 4, 5, 0]);
+
+        // The same move seen from the other direction: (1, 0) and (4, 3) become (1, 4) and (0, 3)
+        let mut tour = Tour::from_route(&[0, 1, 2, 3, 4, 5, 0]);
+        tour.two_opt_move(1, 0, 4, 3);
+        let route = tour.into_route(0);
+        assert!(route == vec![0, 3, 2, 1, 4, 5, 0] || route == vec![0, 5, 4, 1, 2, 3, 0]);
+    }
+
+    #[test]
+    fn test_three_opt_removes_crossings() {
+        let points = [
+            (9.0, 14.0), (0.0, 11.0), (14.0, 10.0), (5.0, 11.0), (19.0, 14.0), (10.0, 0.0),
+            (1.0, 7.0), (15.0, 16.0), (6.0, 8.0), (20.0, 2.0), (11.0, 17.0), (2.0, 15.0),
+        ];
+        let cities: Vec<City> = points.iter().enumerate().map(|(i, &(x, y))| City::new(i as u32, x, y)).collect();
+        let distances = DistanceMatrix::from_cities(&cities);
+        let neighbors = NeighborLists::new(&distances, 10);
+
+        // Pairs of tour edges that cross each other
+        let crossings = |route: &[usize]| {
+            let turn = |a: usize, b: usize, c: usize| {
+                let ((ax, ay), (bx, by), (cx, cy)) = (points[a], points[b], points[c]);
+                ((bx - ax) * (cy - ay) - (by - ay) * (cx - ax)).signum()
+            };
+            let edges: Vec<&[usize]> = route.windows(2).collect();
+            let mut count = 0;
+            for (i, e) in edges.iter().enumerate() {
+                for f in &edges[i + 1..] {
+                    let (a, b, c, d) = (e[0], e[1], f[0], f[1]);
+                    if a != d && b != c && turn(a, b, c) * turn(a, b, d) < 0.0 && turn(c, d, a) * turn(c, d, b) < 0.0 {
+                        count += 1;
+                    }
+                }
+            }
+            count
+        };
+
+        let start: Vec<usize> = (0..12).chain([0]).collect();
+        assert!(crossings(&start) > 0);
+        let mut route = start.clone();
+        three_opt(&mut route, &distances, &neighbors);
+        assert!(distances.route_length(&route) <= distances.route_length(&start) + 1e-9);
+
+        let mut route = start.clone();
+        let gain = improve(&mut route, LocalSearchOperator::ThreeOpt, &distances, &neighbors);
+        assert_eq!(crossings(&route), 0);
+        assert!((distances.route_length(&start) - gain - distances.route_length(&route)).abs() < 1e-9);
+    }
+}
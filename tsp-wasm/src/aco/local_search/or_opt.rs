@@ -0,0 +1,148 @@
+use super::{ActiveCities, Tour, EPSILON};
+use crate::geometry::{distance::DistanceMatrix, neighbors::NeighborLists};
+
+const MAX_SEGMENT_LENGTH: usize = 3;
+
+// Or-opt: moves segments of up to three consecutive cities to a better place in the tour,
+// optionally reversed. Insertion points are taken from the neighbour lists of the segment
+// ends. Returns the distance saved.
+pub fn or_opt(route: &mut Vec<usize>, distances: &DistanceMatrix, neighbors: &NeighborLists) -> f64 {
+    if route.len() < 6 {
+        return 0.0;
+    }
+
+    let start = route[0];
+    let mut tour = Tour::from_route(route);
+    let mut active = ActiveCities::new(&tour);
+
+    let mut total_gain = 0.0;
+    while let Some(first) = active.pop() {
+        if let Some((gain, touched)) = move_segment(&mut tour, first, distances, neighbors) {
+            total_gain += gain;
+            for city in touched {
+                active.activate(city);
+            }
+        }
+    }
+
+    if total_gain > 0.0 {
+        *route = tour.into_route(start);
+    }
+    total_gain
+}
+
+fn move_segment(
+    tour: &mut Tour,
+    first: usize,
+    distances: &DistanceMatrix,
+    neighbors: &NeighborLists,
+) -> Option<(f64, [usize; 6])> {
+    let n = tour.len();
+    let prev = tour.prev(first);
+    let mut last = first;
+
+    for len in 1..=MAX_SEGMENT_LENGTH.min(n - 3) {
+        if len > 1 {
+            last = tour.next(last);
+        }
+        let next = tour.next(last);
+        let removal = distances.get(prev, first) + distances.get(last, next) - distances.get(prev, next);
+        if removal <= EPSILON {
+            continue;
+        }
+
+        let in_segment = |city: usize| tour.offset(first, city) < len;
+        for end in [first, last] {
+            for &c in neighbors.neighbors(end) {
+                if distances.get(end, c) >= removal {
+                    break;
+                }
+                if in_segment(c) {
+                    continue;
+                }
+
+                for (u, v) in [(c, tour.next(c)), (tour.prev(c), c)] {
+                    if in_segment(u) || in_segment(v) {
+                        continue;
+                    }
+                    let base = distances.get(u, v);
+                    let forward = distances.get(u, first) + distances.get(last, v) - base;
+                    let reversed = distances.get(u, last) + distances.get(first, v) - base;
+                    let (insertion, reverse) = if reversed < forward {
+                        (reversed, true)
+                    } else {
+                        (forward, false)
+                    };
+
+                    let gain = removal - insertion;
+                    if gain > EPSILON {
+                        let mut segment = tour.path(first, last);
+                        if reverse {
+                            segment.reverse();
+                        }
+                        let mut order = Vec::with_capacity(n);
+                        for city in tour.path(next, prev) {
+                            order.push(city);
+                            if city == u {
+                                order.extend_from_slice(&segment);
+                            }
+                        }
+                        tour.set_order(order);
+                        return Some((gain, [prev, first, last, next, u, v]));
+                    }
+                }
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::city::City;
+
+    fn line_cities() -> Vec<City> {
+        vec![
+            City::new(0, 0.0, 0.0),
+            City::new(1, 10.0, 0.0),
+            City::new(2, 20.0, 0.0),
+            City::new(3, 30.0, 0.0),
+            City::new(4, 30.0, 10.0),
+            City::new(5, 0.0, 10.0),
+        ]
+    }
+
+    #[test]
+    fn test_or_opt_relocates_city() {
+        let cities = line_cities();
+        let distances = DistanceMatrix::from_cities(&cities);
+        let neighbors = NeighborLists::new(&distances, 5);
+
+        // City 1 is visited on the way back instead of between 0 and 2
+        let mut route = vec![0, 2, 3, 4, 5, 1, 0];
+        let before = distances.route_length(&route);
+        let gain = or_opt(&mut route, &distances, &neighbors);
+
+        assert_eq!(distances.route_length(&route), 80.0);
+        assert!((before - 80.0 - gain).abs() < 1e-9);
+        assert_eq!(route[0], 0);
+        assert_eq!(*route.last().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_or_opt_moves_segment() {
+        let cities = line_cities();
+        let distances = DistanceMatrix::from_cities(&cities);
+        let neighbors = NeighborLists::new(&distances, 5);
+
+        let mut route = vec![0, 3, 4, 5, 1, 2, 0];
+        or_opt(&mut route, &distances, &neighbors);
+
+        let mut visited = route[..6].to_vec();
+        visited.sort();
+        assert_eq!(visited, vec![0, 1, 2, 3, 4, 5]);
+        assert_eq!(distances.route_length(&route), 80.0);
+    }
+}
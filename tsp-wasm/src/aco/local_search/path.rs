@@ -0,0 +1,119 @@
+use crate::geometry::{distance::DistanceMatrix, neighbors::NeighborLists, route::RouteMode};
+
+// The tour operators only handle closed tours. An open path becomes one by adding a dummy city
+// that closes it: the dummy is free to reach the fixed endpoints (or any city for a free end)
+// and prohibitively expensive to reach anything else, so improving moves keep the endpoints.
+#[derive(Debug, Clone)]
+pub struct PathSearchSpace {
+    mode: RouteMode,
+    distances: DistanceMatrix,
+    neighbors: NeighborLists,
+}
+
+impl PathSearchSpace {
+    // None for closed tours, which the operators handle directly
+    pub fn new(distances: &DistanceMatrix, mode: RouteMode, num_neighbors: usize) -> Option<Self> {
+        if mode.is_closed() {
+            return None;
+        }
+
+        let size = distances.size();
+        let longest = (0..size)
+            .flat_map(|i| (0..size).map(move |j| (i, j)))
+            .map(|(i, j)| distances.get(i, j))
+            .fold(0.0, f64::max);
+        let penalty = (longest + 1.0) * (size + 1) as f64;
+        let dummy_cost = |city: usize| match mode {
+            RouteMode::FixedStart { start } if city != start => penalty,
+            RouteMode::FixedStartEnd { start, end } if city != start && city != end => penalty,
+            _ => 0.0,
+        };
+
+        let augmented = DistanceMatrix::from_fn(size + 1, |i, j| {
+            if i == size {
+                dummy_cost(j)
+            } else if j == size {
+                dummy_cost(i)
+            } else {
+                distances.get(i, j)
+            }
+        });
+        let neighbors = NeighborLists::new(&augmented, num_neighbors);
+        Some(PathSearchSpace { mode, distances: augmented, neighbors })
+    }
+
+    // Closes `route` with the dummy city, lets `search` improve the tour and opens it again with
+    // the fixed start first; returns the distance saved on the path
+    pub fn improve(
+        &self,
+        route: &mut Vec<usize>,
+        search: impl FnOnce(&mut Vec<usize>, &DistanceMatrix, &NeighborLists) -> f64,
+    ) -> f64 {
+        let dummy = self.distances.size() - 1;
+        let mut tour = Vec::with_capacity(route.len() + 2);
+        tour.push(dummy);
+        tour.extend_from_slice(route);
+        tour.push(dummy);
+
+        search(&mut tour, &self.distances, &self.neighbors);
+
+        tour.pop();
+        let position = tour.iter().position(|&city| city == dummy).unwrap_or(0);
+        tour.rotate_left(position);
+        let mut path = tour.split_off(1);
+        if self.mode.start().is_some_and(|start| path.last() == Some(&start)) {
+            path.reverse();
+        }
+
+        let before = self.distances.route_length(route);
+        let after = self.distances.route_length(&path);
+        let endpoints_kept = self.mode.start().is_none_or(|start| path.first() == Some(&start))
+            && self.mode.end().is_none_or(|end| path.last() == Some(&end));
+        if !endpoints_kept || after >= before {
+            return 0.0;
+        }
+        *route = path;
+        before - after
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aco::local_search::{improve, LocalSearchOperator};
+    use crate::geometry::city::City;
+
+    fn line() -> DistanceMatrix {
+        DistanceMatrix::from_cities(&(0..6).map(|i| City::new(i, 10.0 * i as f64, 0.0)).collect::<Vec<_>>())
+    }
+
+    fn two_opt(route: &mut Vec<usize>, distances: &DistanceMatrix, neighbors: &NeighborLists) -> f64 {
+        improve(route, LocalSearchOperator::TwoOpt, distances, neighbors)
+    }
+
+    #[test]
+    fn test_closed_tours_need_no_search_space() {
+        assert!(PathSearchSpace::new(&line(), RouteMode::Closed, 5).is_none());
+    }
+
+    #[test]
+    fn test_open_path_is_straightened() {
+        let space = PathSearchSpace::new(&line(), RouteMode::Open, 5).unwrap();
+        let mut route = vec![2, 0, 4, 1, 5, 3];
+        let gain = space.improve(&mut route, two_opt);
+
+        assert_eq!(line().route_length(&route), 50.0);
+        assert_eq!(gain, 150.0 - 50.0);
+    }
+
+    #[test]
+    fn test_fixed_endpoints_are_kept() {
+        let mode = RouteMode::FixedStartEnd { start: 2, end: 0 };
+        let space = PathSearchSpace::new(&line(), mode, 5).unwrap();
+        let mut route = vec![2, 5, 1, 4, 3, 0];
+        space.improve(&mut route, two_opt);
+
+        assert_eq!(route, vec![2, 3, 4, 5, 1, 0]);
+        assert_eq!(line().route_length(&route), 80.0);
+    }
+}
@@ -0,0 +1,236 @@
+use super::{ActiveCities, Tour, EPSILON};
+use crate::geometry::{distance::DistanceMatrix, neighbors::NeighborLists};
+
+// How the three segments a | S1 = b..c | S2 = d..e | f..a are put back together
+#[derive(Debug, Clone, Copy)]
+enum Reconnection {
+    // a -> d..e -> b..c -> f
+    Swap,
+    // a -> d..e -> c..b -> f
+    SwapReverseFirst,
+    // a -> e..d -> b..c -> f
+    SwapReverseSecond,
+    // a -> c..b -> e..d -> f
+    ReverseBoth,
+}
+
+// Neighbour-list driven 3-opt: removes edges (a, b), (c, d), (e, f) and reconnects the
+// segments with one of the pure 3-opt moves (segment swap, with or without reversals).
+// Plain 2-opt moves are left to `two_opt`. Returns the distance saved.
+pub fn three_opt(route: &mut Vec<usize>, distances: &DistanceMatrix, neighbors: &NeighborLists) -> f64 {
+    if route.len() < 6 {
+        return 0.0;
+    }
+
+    let start = route[0];
+    let mut tour = Tour::from_route(route);
+    let mut active = ActiveCities::new(&tour);
+
+    let mut total_gain = 0.0;
+    while let Some(a) = active.pop() {
+        if let Some((gain, [b, c, d, e, f], reconnection)) = find_move(&tour, a, distances, neighbors) {
+            apply(&mut tour, [a, b, c, d, e, f], reconnection);
+            total_gain += gain;
+            for city in [a, b, c, d, e, f] {
+                active.activate(city);
+            }
+        }
+    }
+
+    if total_gain > 0.0 {
+        *route = tour.into_route(start);
+    }
+    total_gain
+}
+
+fn find_move(
+    tour: &Tour,
+    a: usize,
+    distances: &DistanceMatrix,
+    neighbors: &NeighborLists,
+) -> Option<(f64, [usize; 5], Reconnection)> {
+    let n = tour.len();
+    let dist = |i: usize, j: usize| distances.get(i, j);
+    // Position after `a` along the tour, with `a` itself counted as the end (n)
+    let rel = |city: usize| match tour.offset(a, city) {
+        0 => n,
+        offset => offset,
+    };
+
+    let b = tour.next(a);
+    let d_ab = dist(a, b);
+
+    // New edge (a, d): S2 is moved in front of S1
+    for &d in neighbors.neighbors(a) {
+        let g1 = d_ab - dist(a, d);
+        if g1 <= EPSILON {
+            break;
+        }
+        if d == b {
+            continue;
+        }
+        let c = tour.prev(d);
+        let d_cd = dist(c, d);
+
+        for (link, reconnection) in [(b, Reconnection::Swap), (c, Reconnection::SwapReverseFirst)] {
+            for &e in neighbors.neighbors(link) {
+                let g2 = g1 + d_cd - dist(e, link);
+                if g2 <= EPSILON {
+                    break;
+                }
+                if e == a || rel(e) < rel(d) {
+                    continue;
+                }
+                let f = tour.next(e);
+                // The remaining end of S1 closes the tour at f
+                let other = if link == b { c } else { b };
+                let gain = g2 + dist(e, f) - dist(other, f);
+                if gain > EPSILON {
+                    return Some((gain, [b, c, d, e, f], reconnection));
+                }
+            }
+        }
+    }
+
+    // New edge (a, e): S2 is reversed and moved in front of S1
+    for &e in neighbors.neighbors(a) {
+        let g1 = d_ab - dist(a, e);
+        if g1 <= EPSILON {
+            break;
+        }
+        if e == b {
+            continue;
+        }
+        let f = tour.next(e);
+        let d_ef = dist(e, f);
+
+        for &d in neighbors.neighbors(b) {
+            let g2 = g1 + d_ef - dist(d, b);
+            if g2 <= EPSILON {
+                break;
+            }
+            if rel(d) < 2 || rel(d) > rel(e) {
+                continue;
+            }
+            let c = tour.prev(d);
+            let gain = g2 + dist(c, d) - dist(c, f);
+            if gain > EPSILON {
+                return Some((gain, [b, c, d, e, f], Reconnection::SwapReverseSecond));
+            }
+        }
+    }
+
+    // New edge (a, c): both segments stay in place but are reversed
+    for &c in neighbors.neighbors(a) {
+        let g1 = d_ab - dist(a, c);
+        if g1 <= EPSILON {
+            break;
+        }
+        let d = tour.next(c);
+        if c == b || d == a {
+            continue;
+        }
+        let d_cd = dist(c, d);
+
+        for &e in neighbors.neighbors(b) {
+            let g2 = g1 + d_cd - dist(b, e);
+            if g2 <= EPSILON {
+                break;
+            }
+            if e == a || rel(e) < rel(d) {
+                continue;
+            }
+            let f = tour.next(e);
+            let gain = g2 + dist(e, f) - dist(d, f);
+            if gain > EPSILON {
+                return Some((gain, [b, c, d, e, f], Reconnection::ReverseBoth));
+            }
+        }
+    }
+
+    None
+}
+
+fn apply(tour: &mut Tour, [a, b, c, d, e, f]: [usize; 6], reconnection: Reconnection) {
+    let mut first = tour.path(b, c);
+    let mut second = tour.path(d, e);
+    let rest = tour.path(f, a);
+
+    let mut order = Vec::with_capacity(tour.len());
+    match reconnection {
+        Reconnection::Swap => {
+            order.append(&mut second);
+            order.append(&mut first);
+        }
+        Reconnection::SwapReverseFirst => {
+            first.reverse();
+            order.append(&mut second);
+            order.append(&mut first);
+        }
+        Reconnection::SwapReverseSecond => {
+            second.reverse();
+            order.append(&mut second);
+            order.append(&mut first);
+        }
+        Reconnection::ReverseBoth => {
+            first.reverse();
+            second.reverse();
+            order.append(&mut first);
+            order.append(&mut second);
+        }
+    }
+    order.extend(rest);
+    tour.set_order(order);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aco::local_search::two_opt;
+    use crate::geometry::city::City;
+
+    fn grid(size: usize) -> Vec<City> {
+        (0..size * size)
+            .map(|i| City::new(i as u32, (i % size) as f64 * 10.0, (i / size) as f64 * 10.0))
+            .collect()
+    }
+
+    fn assert_permutation(route: &[usize], n: usize) {
+        assert_eq!(route.first(), route.last());
+        let mut visited = route[..n].to_vec();
+        visited.sort();
+        assert_eq!(visited, (0..n).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_three_opt_segment_swap() {
+        let cities: Vec<City> = (0..8).map(|i| City::new(i, i as f64 * 10.0, 0.0)).collect();
+        let distances = DistanceMatrix::from_cities(&cities);
+        let neighbors = NeighborLists::new(&distances, 7);
+
+        // Pure segment swap: 2-opt alone cannot fix it without reversing a segment
+        let mut route = vec![0, 4, 5, 1, 2, 3, 6, 7, 0];
+        let before = distances.route_length(&route);
+        let gain = three_opt(&mut route, &distances, &neighbors);
+
+        assert_permutation(&route, 8);
+        assert!(gain > 0.0);
+        assert!((before - gain - distances.route_length(&route)).abs() < 1e-9);
+        assert_eq!(distances.route_length(&route), 140.0);
+    }
+
+    #[test]
+    fn test_three_opt_after_two_opt_never_worse() {
+        let cities = grid(5);
+        let distances = DistanceMatrix::from_cities(&cities);
+        let neighbors = NeighborLists::new(&distances, 8);
+
+        let mut route: Vec<usize> = vec![0, 12, 3, 20, 7, 18, 1, 24, 9, 5, 14, 22, 2, 16, 11, 4, 19, 8, 23, 6, 15, 10, 21, 13, 17, 0];
+        two_opt(&mut route, &distances, &neighbors);
+        let after_two_opt = distances.route_length(&route);
+
+        let gain = three_opt(&mut route, &distances, &neighbors);
+        assert_permutation(&route, 25);
+        assert!((after_two_opt - gain - distances.route_length(&route)).abs() < 1e-9);
+    }
+}
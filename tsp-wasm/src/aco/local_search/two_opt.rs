@@ -0,0 +1,127 @@
+use super::{ActiveCities, Tour, EPSILON};
+use crate::geometry::{distance::DistanceMatrix, neighbors::NeighborLists};
+
+// First-improvement 2-opt restricted to neighbour lists, with don't-look bits so only
+// cities next to a recent change are re-examined. Returns the distance saved.
+pub fn two_opt(route: &mut Vec<usize>, distances: &DistanceMatrix, neighbors: &NeighborLists) -> f64 {
+    if route.len() < 5 {
+        return 0.0;
+    }
+
+    let start = route[0];
+    let mut tour = Tour::from_route(route);
+    let mut active = ActiveCities::new(&tour);
+
+    let mut total_gain = 0.0;
+    while let Some(a) = active.pop() {
+        if let Some((gain, touched)) = improve_city(&mut tour, a, distances, neighbors) {
+            total_gain += gain;
+            for city in touched {
+                active.activate(city);
+            }
+        }
+    }
+
+    if total_gain > 0.0 {
+        *route = tour.into_route(start);
+    }
+    total_gain
+}
+
+fn improve_city(
+    tour: &mut Tour,
+    a: usize,
+    distances: &DistanceMatrix,
+    neighbors: &NeighborLists,
+) -> Option<(f64, [usize; 4])> {
+    // Successor direction: replace (a, succ a) and (c, succ c) with (a, c) and (succ a, succ c)
+    let b = tour.next(a);
+    let d_ab = distances.get(a, b);
+    for &c in neighbors.neighbors(a) {
+        let d_ac = distances.get(a, c);
+        if d_ac >= d_ab {
+            break;
+        }
+        let d = tour.next(c);
+        if c == b || d == a {
+            continue;
+        }
+        let delta = d_ac + distances.get(b, d) - d_ab - distances.get(c, d);
+        if delta < -EPSILON {
+            tour.reverse_path(b, c);
+            return Some((-delta, [a, b, c, d]));
+        }
+    }
+
+    // Predecessor direction: replace (pred a, a) and (pred c, c) with (a, c) and (pred a, pred c)
+    let b = tour.prev(a);
+    let d_ba = distances.get(b, a);
+    for &c in neighbors.neighbors(a) {
+        let d_ac = distances.get(a, c);
+        if d_ac >= d_ba {
+            break;
+        }
+        let d = tour.prev(c);
+        if c == b || d == a {
+            continue;
+        }
+        let delta = d_ac + distances.get(b, d) - d_ba - distances.get(d, c);
+        if delta < -EPSILON {
+            tour.reverse_path(a, d);
+            return Some((-delta, [a, b, c, d]));
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::city::City;
+
+    fn circle(n: usize) -> Vec<City> {
+        (0..n)
+            .map(|i| {
+                let angle = i as f64 * std::f64::consts::TAU / n as f64;
+                City::new(i as u32, 100.0 * angle.cos(), 100.0 * angle.sin())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_two_opt_removes_crossing() {
+        let cities = vec![
+            City::new(0, 0.0, 0.0),
+            City::new(1, 10.0, 0.0),
+            City::new(2, 10.0, 10.0),
+            City::new(3, 0.0, 10.0),
+        ];
+        let distances = DistanceMatrix::from_cities(&cities);
+        let neighbors = NeighborLists::new(&distances, 3);
+
+        let mut route = vec![0, 2, 1, 3, 0];
+        let before = distances.route_length(&route);
+        let gain = two_opt(&mut route, &distances, &neighbors);
+
+        assert_eq!(route[0], 0);
+        assert_eq!(distances.route_length(&route), 40.0);
+        assert!((before - 40.0 - gain).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_two_opt_untangles_circle() {
+        let cities = circle(12);
+        let distances = DistanceMatrix::from_cities(&cities);
+        let neighbors = NeighborLists::new(&distances, 6);
+        let optimum = distances.route_length(&(0..12).chain(std::iter::once(0)).collect::<Vec<_>>());
+
+        let mut route = vec![0, 6, 1, 7, 2, 8, 3, 9, 4, 10, 5, 11, 0];
+        two_opt(&mut route, &distances, &neighbors);
+
+        let mut visited = route[..12].to_vec();
+        visited.sort();
+        assert_eq!(visited, (0..12).collect::<Vec<_>>());
+        assert!((distances.route_length(&route) - optimum).abs() < 1e-9);
+    }
+}
@@ -1,3 +1,13 @@
 pub mod ant;
+pub mod choice;
 pub mod pheromone;
-pub mod colony;
\ No newline at end of file
+pub mod precedence;
+pub mod problem;
+pub mod prize;
+pub mod colony;
+pub mod update;
+pub mod local_search;
+pub mod vrp;
+pub mod mtsp;
+pub mod qap;
+pub mod pareto;
@@ -0,0 +1,319 @@
+use crate::aco::{ant::Ant, choice::inverse_distance, problem::Problem, vrp::split_at_depot};
+use crate::geometry::distance::DistanceMatrix;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SalesmenObjective {
+    // Sum of all tour lengths
+    #[default]
+    TotalDistance,
+    // Length of the longest tour
+    MinMax,
+}
+
+impl SalesmenObjective {
+    pub fn from_name(name: &str) -> Option<SalesmenObjective> {
+        match name.to_ascii_lowercase().as_str() {
+            "total" | "total-distance" | "minsum" | "min-sum" => Some(SalesmenObjective::TotalDistance),
+            "minmax" | "min-max" | "longest" => Some(SalesmenObjective::MinMax),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SalesmenObjective::TotalDistance => "total",
+            SalesmenObjective::MinMax => "minmax",
+        }
+    }
+}
+
+// Multiple travelling salesmen (mTSP): every salesman leaves the shared depot, visits at least one
+// city and returns. Solutions are giant tours like vehicle routes, with exactly one depot visit
+// between consecutive salesmen. Ants build a single tour through all cities which is then cut
+// into the best m consecutive sub-tours for the objective.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MultipleSalesmen {
+    depot: usize,
+    salesmen: usize,
+    objective: SalesmenObjective,
+    num_cities: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SalesmanTour {
+    pub salesman: usize,
+    pub route: Vec<usize>,
+    pub distance: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SalesmenSolution {
+    pub objective: &'static str,
+    pub total_distance: f64,
+    pub longest_tour: f64,
+    pub tours: Vec<SalesmanTour>,
+}
+
+impl MultipleSalesmen {
+    // None unless the depot exists and every salesman can get a city of their own
+    pub fn new(depot: usize, salesmen: usize, objective: SalesmenObjective, num_cities: usize) -> Option<Self> {
+        let valid = depot < num_cities && salesmen >= 1 && salesmen < num_cities;
+        valid.then_some(MultipleSalesmen { depot, salesmen, objective, num_cities })
+    }
+
+    pub fn depot(&self) -> usize {
+        self.depot
+    }
+
+    pub fn salesmen(&self) -> usize {
+        self.salesmen
+    }
+
+    pub fn objective(&self) -> SalesmenObjective {
+        self.objective
+    }
+
+    pub fn num_cities(&self) -> usize {
+        self.num_cities
+    }
+
+    pub fn tours(&self, route: &[usize]) -> Vec<Vec<usize>> {
+        split_at_depot(route, self.depot)
+    }
+
+    // Objective value of a giant tour
+    pub fn cost(&self, route: &[usize], distances: &DistanceMatrix) -> f64 {
+        let lengths = self.tours(route).into_iter().map(|tour| distances.route_length(&tour));
+        match self.objective {
+            SalesmenObjective::TotalDistance => lengths.sum(),
+            SalesmenObjective::MinMax => lengths.fold(0.0, f64::max),
+        }
+    }
+
+    // Cuts a tour through all cities into the m sub-tours that are best for the objective while
+    // keeping the order of the cities (dynamic programming over the cut positions)
+    pub fn split(&self, tour: &[usize], distances: &DistanceMatrix) -> Vec<usize> {
+        let depot = self.depot;
+        let customers: Vec<usize> = tour.iter().copied().filter(|&city| city != depot).collect();
+        let n = customers.len();
+        let m = self.salesmen.min(n);
+
+        // inner[j] is the length of customers[0..=j] travelled in order
+        let mut inner = vec![0.0; n];
+        for j in 1..n {
+            inner[j] = inner[j - 1] + distances.get(customers[j - 1], customers[j]);
+        }
+        let tour_length = |i: usize, j: usize| {
+            distances.get(depot, customers[i]) + inner[j] - inner[i] + distances.get(customers[j], depot)
+        };
+        let combine = |a: f64, b: f64| match self.objective {
+            SalesmenObjective::TotalDistance => a + b,
+            SalesmenObjective::MinMax => a.max(b),
+        };
+
+        // best[k][j]: k salesmen covering the first j customers; cut[k][j] where the last one starts
+        let mut best = vec![vec![f64::INFINITY; n + 1]; m + 1];
+        let mut cut = vec![vec![0; n + 1]; m + 1];
+        best[0][0] = 0.0;
+        for k in 1..=m {
+            for j in k..=n - (m - k) {
+                for i in k - 1..j {
+                    let cost = combine(best[k - 1][i], tour_length(i, j - 1));
+                    if cost < best[k][j] {
+                        best[k][j] = cost;
+                        cut[k][j] = i;
+                    }
+                }
+            }
+        }
+
+        let mut bounds = Vec::with_capacity(m);
+        let mut j = n;
+        for k in (1..=m).rev() {
+            bounds.push((cut[k][j], j));
+            j = cut[k][j];
+        }
+        let mut route = vec![depot];
+        for (start, end) in bounds.into_iter().rev() {
+            route.extend_from_slice(&customers[start..end]);
+            route.push(depot);
+        }
+        route
+    }
+
+    pub fn solution(&self, route: &[usize], distances: &DistanceMatrix) -> SalesmenSolution {
+        let tours: Vec<SalesmanTour> = self
+            .tours(route)
+            .into_iter()
+            .enumerate()
+            .map(|(salesman, route)| {
+                let distance = distances.route_length(&route);
+                SalesmanTour { salesman, route, distance }
+            })
+            .collect();
+        SalesmenSolution {
+            objective: self.objective.name(),
+            total_distance: tours.iter().map(|tour| tour.distance).sum(),
+            longest_tour: tours.iter().map(|tour| tour.distance).fold(0.0, f64::max),
+            tours,
+        }
+    }
+}
+
+// mTSP as a colony problem: ants build one closed tour from the depot which is split on completion
+#[derive(Debug, Clone)]
+pub struct MultipleTsp {
+    salesmen: MultipleSalesmen,
+    distances: DistanceMatrix,
+}
+
+impl MultipleTsp {
+    // None if the salesmen were set up for a different number of cities
+    pub fn new(salesmen: MultipleSalesmen, distances: DistanceMatrix) -> Option<Self> {
+        (salesmen.num_cities() == distances.size()).then_some(MultipleTsp { salesmen, distances })
+    }
+}
+
+impl Problem for MultipleTsp {
+    fn size(&self) -> usize {
+        self.distances.size()
+    }
+
+    fn is_symmetric(&self) -> bool {
+        self.distances.is_symmetric()
+    }
+
+    fn heuristic(&self, from: usize, to: usize) -> f64 {
+        inverse_distance(&self.distances, from, to)
+    }
+
+    fn new_ant(&self, _index: usize) -> Ant {
+        Ant::new(self.salesmen.depot, self.size())
+    }
+
+    fn step(&self, ant: &mut Ant, next: Option<usize>) {
+        match next {
+            Some(city) => ant.move_to_city(city, &self.distances),
+            None => ant.complete_tour(&self.distances),
+        }
+    }
+
+    fn finish(&self, ant: &mut Ant) {
+        let route = self.salesmen.split(ant.route(), &self.distances);
+        let cost = self.salesmen.cost(&route, &self.distances);
+        ant.replace_route(route, cost);
+    }
+
+    fn cost(&self, solution: &[usize]) -> f64 {
+        self.salesmen.cost(solution, &self.distances)
+    }
+
+    fn supports_local_search(&self) -> bool {
+        self.is_symmetric()
+    }
+
+    fn depot(&self) -> Option<usize> {
+        Some(self.salesmen.depot)
+    }
+
+    // Every salesman's tour with the total and longest tour length
+    fn summary(&self, solution: &[usize]) -> Value {
+        json!(self.salesmen.solution(solution, &self.distances))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aco::colony::{ACOParameters, Colony};
+    use crate::aco::local_search::LocalSearchMode;
+    use crate::geometry::city::City;
+
+    // Depot in the middle with two cities on either side
+    fn star() -> DistanceMatrix {
+        DistanceMatrix::from_cities(&[
+            City::new(0, 0.0, 0.0),
+            City::new(1, 10.0, 0.0),
+            City::new(2, 20.0, 0.0),
+            City::new(3, -10.0, 0.0),
+            City::new(4, -40.0, 0.0),
+        ])
+    }
+
+    #[test]
+    fn test_salesmen_validation() {
+        assert!(MultipleSalesmen::new(0, 2, SalesmenObjective::MinMax, 5).is_some());
+        assert!(MultipleSalesmen::new(0, 5, SalesmenObjective::MinMax, 5).is_none());
+        assert!(MultipleSalesmen::new(5, 2, SalesmenObjective::MinMax, 5).is_none());
+        assert_eq!(SalesmenObjective::from_name("min-max"), Some(SalesmenObjective::MinMax));
+    }
+
+    #[test]
+    fn test_split_total_distance() {
+        let salesmen = MultipleSalesmen::new(0, 2, SalesmenObjective::TotalDistance, 5).unwrap();
+        let route = salesmen.split(&[0, 1, 2, 3, 4, 0], &star());
+        assert_eq!(route, vec![0, 1, 2, 0, 3, 4, 0]);
+        assert_eq!(salesmen.cost(&route, &star()), 40.0 + 80.0);
+    }
+
+    #[test]
+    fn test_split_min_max() {
+        // Three salesmen: nothing beats the round trip to the far city 4
+        let salesmen = MultipleSalesmen::new(0, 3, SalesmenObjective::MinMax, 5).unwrap();
+        let route = salesmen.split(&[0, 1, 2, 3, 4, 0], &star());
+        assert_eq!(salesmen.tours(&route).len(), 3);
+        assert_eq!(salesmen.cost(&route, &star()), 80.0);
+
+        let solution = salesmen.solution(&route, &star());
+        assert_eq!(solution.longest_tour, 80.0);
+        assert_eq!(solution.tours.iter().map(|tour| tour.distance).sum::<f64>(), solution.total_distance);
+        assert!(solution.tours.iter().all(|tour| tour.route.len() >= 3));
+    }
+
+    #[test]
+    fn test_multiple_salesmen_colony() {
+        // Two mirrored clusters on either side of the depot
+        let cities = vec![
+            City::new(0, 0.0, 0.0),
+            City::new(1, 10.0, 0.0),
+            City::new(2, 20.0, 0.0),
+            City::new(3, 10.0, 10.0),
+            City::new(4, -10.0, 0.0),
+            City::new(5, -20.0, 0.0),
+            City::new(6, -10.0, -10.0),
+        ];
+        let params = ACOParameters {
+            num_ants: 7,
+            max_generations: 20,
+            seed: Some(3),
+            local_search: LocalSearchMode::AllAnts,
+            ..Default::default()
+        };
+        let mut colony = Colony::new(cities, params);
+        let five = MultipleSalesmen::new(0, 2, SalesmenObjective::MinMax, 5).unwrap();
+        assert!(MultipleTsp::new(five, colony.distances().clone()).is_none());
+        let salesmen = MultipleSalesmen::new(0, 2, SalesmenObjective::MinMax, 7).unwrap();
+        assert!(colony.set_problem(Box::new(MultipleTsp::new(salesmen, colony.distances().clone()).unwrap())));
+        while colony.run_iteration() {}
+
+        let summary = colony.best_summary();
+        assert_eq!(summary["tours"].as_array().unwrap().len(), 2);
+        let longest = 20.0 + 2.0 * 200f64.sqrt();
+        assert!((colony.best_distance() - longest).abs() < 1e-9);
+        assert!((summary["longest_tour"].as_f64().unwrap() - longest).abs() < 1e-9);
+        assert!((summary["total_distance"].as_f64().unwrap() - 2.0 * longest).abs() < 1e-9);
+
+        // One route per salesman, each from the depot through one cluster and back
+        let routes = colony.best_vehicle_routes();
+        assert_eq!(routes.len(), 2);
+        for route in &routes {
+            assert_eq!((route[0], route[route.len() - 1]), (0, 0));
+        }
+        let mut visited: Vec<Vec<usize>> = routes.iter().map(|route| route[1..route.len() - 1].to_vec()).collect();
+        visited.iter_mut().for_each(|cities| cities.sort());
+        visited.sort();
+        assert_eq!(visited, vec![vec![1, 2, 3], vec![4, 5, 6]]);
+    }
+}
@@ -0,0 +1,318 @@
+use crate::aco::{ant::Ant, choice::ChoiceInfo, colony::ACOParameters, pheromone::PheromoneMatrix};
+use crate::geometry::distance::DistanceMatrix;
+use rand::Rng;
+use serde::Serialize;
+use std::cmp::Ordering;
+
+// Whether cost vector `a` is at least as good as `b` in every criterion and better in one
+pub fn dominates(a: &[f64], b: &[f64]) -> bool {
+    a.iter().zip(b).all(|(x, y)| x <= y) && a.iter().zip(b).any(|(x, y)| x < y)
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ParetoTour {
+    pub route: Vec<usize>,
+    // One cost per criterion
+    pub costs: Vec<f64>,
+}
+
+// The non-dominated tours found so far; tours with equal costs are kept once
+#[derive(Debug, Clone, Default)]
+pub struct ParetoArchive {
+    tours: Vec<ParetoTour>,
+}
+
+impl ParetoArchive {
+    pub fn new() -> Self {
+        ParetoArchive::default()
+    }
+
+    // Adds the tour unless an archived one is at least as good, dropping the tours it dominates;
+    // returns whether it was added
+    pub fn insert(&mut self, route: &[usize], costs: Vec<f64>) -> bool {
+        if self.tours.iter().any(|tour| tour.costs == costs || dominates(&tour.costs, &costs)) {
+            return false;
+        }
+        self.tours.retain(|tour| !dominates(&costs, &tour.costs));
+        self.tours.push(ParetoTour { route: route.to_vec(), costs });
+        true
+    }
+
+    // Sorted by the first criterion, so by decreasing second criterion for two criteria
+    pub fn tours(&self) -> Vec<ParetoTour> {
+        let mut tours = self.tours.clone();
+        tours.sort_by(|a, b| a.costs.partial_cmp(&b.costs).unwrap_or(Ordering::Equal));
+        tours
+    }
+
+    pub fn len(&self) -> usize {
+        self.tours.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tours.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.tours.clear();
+    }
+}
+
+// Multi-pheromone ACO for tours with several cost criteria (e.g. distance, toll and time): one
+// trail and heuristic per criterion. Every ant draws its own weights and follows the weighted
+// product of the criteria's tau^alpha * eta^beta, so the colony spreads along the whole front
+// instead of converging on one compromise; the archived tours deposit on every trail.
+#[derive(Debug, Clone)]
+pub struct MultiObjective {
+    criteria: Vec<DistanceMatrix>,
+    trails: Vec<PheromoneMatrix>,
+    choice_info: Vec<ChoiceInfo>,
+    // Passed to the ants in place of a combined choice info; they weigh the edges themselves
+    uniform: ChoiceInfo,
+    archive: ParetoArchive,
+}
+
+impl MultiObjective {
+    // None unless there are at least two criteria measured between the same cities
+    pub fn new(criteria: Vec<DistanceMatrix>, symmetric: bool, initial_trail: f64, parameters: &ACOParameters) -> Option<Self> {
+        let size = criteria.first()?.size();
+        if criteria.len() < 2 || criteria.iter().any(|matrix| matrix.size() != size) {
+            return None;
+        }
+
+        // Directional trails unless every criterion is symmetric
+        let symmetric = symmetric && criteria.iter().all(DistanceMatrix::is_symmetric);
+        let trail = || {
+            if symmetric {
+                PheromoneMatrix::new(size, initial_trail)
+            } else {
+                PheromoneMatrix::asymmetric(size, initial_trail)
+            }
+        };
+        Some(MultiObjective {
+            trails: criteria.iter().map(|_| trail()).collect(),
+            choice_info: criteria.iter().map(|matrix| ChoiceInfo::new(matrix, parameters.alpha, parameters.beta)).collect(),
+            criteria,
+            uniform: ChoiceInfo::uniform(size),
+            archive: ParetoArchive::new(),
+        })
+    }
+
+    pub fn num_criteria(&self) -> usize {
+        self.criteria.len()
+    }
+
+    pub fn trail(&self, criterion: usize) -> &PheromoneMatrix {
+        &self.trails[criterion]
+    }
+
+    pub fn uniform_choice_info(&self) -> &ChoiceInfo {
+        &self.uniform
+    }
+
+    pub fn archive(&self) -> &ParetoArchive {
+        &self.archive
+    }
+
+    // Weights drawn uniformly from the simplex, so that no trade-off between the criteria is favoured
+    pub fn random_weights<R: Rng>(&self, rng: &mut R) -> Vec<f64> {
+        let draws: Vec<f64> = (0..self.num_criteria()).map(|_| -(1.0 - rng.gen::<f64>()).ln()).collect();
+        let total: f64 = draws.iter().sum();
+        draws.iter().map(|draw| draw / total).collect()
+    }
+
+    // Brings the choice info up to date with the trails, once per generation
+    pub fn refresh(&mut self) {
+        for (info, trail) in self.choice_info.iter_mut().zip(&self.trails) {
+            info.update(trail);
+        }
+    }
+
+    // Product of every criterion's tau^alpha * eta^beta raised to the ant's weight for it
+    pub fn attractiveness(&self, from: usize, to: usize, weights: &[f64]) -> f64 {
+        self.choice_info.iter().zip(weights).map(|(info, &weight)| info.get(from, to).powf(weight)).product()
+    }
+
+    pub fn costs(&self, route: &[usize]) -> Vec<f64> {
+        self.criteria.iter().map(|matrix| matrix.route_length(route)).collect()
+    }
+
+    // Archives the ants' tours, then lets the archive deposit on every trail after evaporation;
+    // each archived tour deposits 1 / cost for each criterion, shared so that the whole archive
+    // deposits as much as a single ant
+    pub fn update(&mut self, ants: &[Ant], evaporation_rate: f64) {
+        for ant in ants {
+            let costs = self.costs(ant.route());
+            self.archive.insert(ant.route(), costs);
+        }
+
+        let share = 1.0 / self.archive.len().max(1) as f64;
+        for (criterion, trail) in self.trails.iter_mut().enumerate() {
+            trail.evaporate(evaporation_rate);
+            for tour in &self.archive.tours {
+                trail.deposit_weighted(&tour.route, tour.costs[criterion], share);
+            }
+        }
+    }
+
+    // Back to uniform trails and an empty archive
+    pub fn reset(&mut self, initial_trail: f64) {
+        for trail in &mut self.trails {
+            trail.fill(initial_trail);
+        }
+        self.archive.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aco::colony::{AcoVariant, Colony};
+    use crate::aco::local_search::LocalSearchMode;
+    use crate::aco::update::HyperCubeUpdate;
+    use crate::geometry::city::City;
+    use rand::{rngs::SmallRng, SeedableRng};
+
+    fn square() -> DistanceMatrix {
+        DistanceMatrix::from_cities(&[
+            City::new(0, 0.0, 0.0),
+            City::new(1, 10.0, 0.0),
+            City::new(2, 10.0, 10.0),
+            City::new(3, 0.0, 10.0),
+        ])
+    }
+
+    #[test]
+    fn test_dominates() {
+        assert!(dominates(&[1.0, 2.0], &[1.0, 3.0]));
+        assert!(!dominates(&[1.0, 2.0], &[1.0, 2.0]));
+        assert!(!dominates(&[1.0, 4.0], &[2.0, 3.0]));
+    }
+
+    #[test]
+    fn test_archive_keeps_non_dominated_tours() {
+        let mut archive = ParetoArchive::new();
+        assert!(archive.insert(&[0, 1, 0], vec![10.0, 5.0]));
+        assert!(archive.insert(&[0, 2, 0], vec![5.0, 10.0]));
+        assert!(!archive.insert(&[0, 3, 0], vec![6.0, 11.0]));
+        assert!(!archive.insert(&[0, 3, 0], vec![5.0, 10.0]));
+        assert_eq!(archive.len(), 2);
+
+        // Dominates both archived tours
+        assert!(archive.insert(&[0, 3, 0], vec![4.0, 4.0]));
+        assert_eq!(archive.tours(), vec![ParetoTour { route: vec![0, 3, 0], costs: vec![4.0, 4.0] }]);
+    }
+
+    #[test]
+    fn test_multi_objective_update() {
+        // The second criterion makes the diagonals cheap
+        let tolls = DistanceMatrix::from_fn(4, |i, j| if i.abs_diff(j) == 2 { 1.0 } else { 10.0 });
+        let parameters = ACOParameters::default();
+        assert!(MultiObjective::new(vec![square()], true, 1.0, &parameters).is_none());
+        let mut objectives = MultiObjective::new(vec![square(), tolls], true, 1.0, &parameters).unwrap();
+
+        let weights = objectives.random_weights(&mut SmallRng::seed_from_u64(1));
+        assert_eq!(weights.len(), 2);
+        assert!((weights.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+
+        let distances = square();
+        let mut around = Ant::new(0, 4);
+        let mut across = Ant::new(0, 4);
+        for city in [1, 2, 3] {
+            around.move_to_city(city, &distances);
+        }
+        for city in [2, 1, 3] {
+            across.move_to_city(city, &distances);
+        }
+        around.complete_tour(&distances);
+        across.complete_tour(&distances);
+        assert_eq!(objectives.costs(around.route()), vec![40.0, 40.0]);
+
+        objectives.update(&[around, across], 0.5);
+        assert_eq!(objectives.archive().len(), 2);
+        // Half the initial trail is left on edge (0, 1) plus its share of 1/40 from the tour around
+        assert!((objectives.trail(0).get(0, 1) - (0.5 + 0.5 / 40.0)).abs() < 1e-12);
+        objectives.refresh();
+        assert!(objectives.attractiveness(0, 2, &[0.0, 1.0]) > objectives.attractiveness(0, 1, &[0.0, 1.0]));
+    }
+
+    #[test]
+    fn test_pareto_front_colony() {
+        // A hexagon with rounded distances, so that equal costs add up exactly
+        let distances = DistanceMatrix::from_fn(6, |i, j| [0.0, 10.0, 17.0, 20.0, 17.0, 10.0][i.abs_diff(j)]);
+        // Tolls on the rim of the hexagon, so that tours criss-crossing it are cheaper
+        let tolls = DistanceMatrix::from_fn(6, |i, j| match i.abs_diff(j) {
+            1 | 5 => 10.0,
+            _ => 1.0 + ((i * j) % 3) as f64,
+        });
+
+        // Every tour from city 0, then the cost vectors no other tour dominates
+        let mut costs: Vec<Vec<f64>> = Vec::new();
+        let mut tour = [0, 1, 2, 3, 4, 5];
+        fn next_permutation(values: &mut [usize]) -> bool {
+            let Some(i) = (0..values.len() - 1).rev().find(|&i| values[i] < values[i + 1]) else {
+                return false;
+            };
+            let j = (i + 1..values.len()).rev().find(|&j| values[j] > values[i]).unwrap();
+            values.swap(i, j);
+            values[i + 1..].reverse();
+            true
+        }
+        loop {
+            let route: Vec<usize> = tour.iter().copied().chain([0]).collect();
+            costs.push(vec![distances.route_length(&route), tolls.route_length(&route)]);
+            if !next_permutation(&mut tour[1..]) {
+                break;
+            }
+        }
+        let mut front: Vec<Vec<f64>> = costs.iter().filter(|a| !costs.iter().any(|b| dominates(b, a))).cloned().collect();
+        front.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        front.dedup();
+        assert!(front.len() > 1);
+
+        let params = ACOParameters {
+            num_ants: 10,
+            max_generations: 50,
+            seed: Some(5),
+            ..Default::default()
+        };
+        let mut colony = Colony::from_cost_matrix(distances, None, params);
+        assert!(!colony.set_criteria(vec![DistanceMatrix::from_fn(5, |_, _| 1.0)]));
+        assert!(colony.set_criteria(vec![tolls]));
+        while colony.run_iteration() {}
+
+        let found = colony.pareto_front();
+        assert_eq!(found.iter().map(|tour| tour.costs.clone()).collect::<Vec<_>>(), front);
+        assert!(found.iter().all(|tour| tour.route.len() == 7));
+        // The shortest tour is one end of the front
+        assert_eq!(found[0].costs[0], colony.best_distance());
+
+        assert!(colony.set_criteria(Vec::new()));
+        assert!(colony.pareto_front().is_empty());
+    }
+
+    #[test]
+    fn test_criteria_need_ant_system() {
+        let tolls = || vec![DistanceMatrix::from_fn(4, |i, j| if i.abs_diff(j) == 2 { 1.0 } else { 10.0 })];
+        let colony = |params: ACOParameters| Colony::from_cost_matrix(square(), None, params);
+
+        // The archive's update takes the place of the variant's, so only Ant System keeps its meaning
+        for variant in [AcoVariant::MaxMin, AcoVariant::AntColonySystem, AcoVariant::Elitist, AcoVariant::RankBased] {
+            assert!(!colony(ACOParameters { variant, ..Default::default() }).set_criteria(tolls()));
+        }
+        let local_search = ACOParameters { local_search: LocalSearchMode::AllAnts, ..Default::default() };
+        assert!(!colony(local_search).set_criteria(tolls()));
+        assert!(!colony(ACOParameters { polish_on_complete: true, ..Default::default() }).set_criteria(tolls()));
+
+        let mut custom = colony(ACOParameters::default());
+        custom.set_update_strategy(Box::new(HyperCubeUpdate { evaporation_rate: 0.1 }));
+        assert!(!custom.set_criteria(tolls()));
+
+        let mut colony = colony(ACOParameters { num_ants: 4, max_generations: 3, ..Default::default() });
+        assert!(colony.set_criteria(tolls()));
+        assert!(!colony.set_update_strategy(Box::new(HyperCubeUpdate { evaporation_rate: 0.1 })));
+        while colony.run_iteration() {}
+        assert_eq!(colony.update_strategy().name(), "as");
+        assert!(!colony.pareto_front().is_empty());
+    }
+}
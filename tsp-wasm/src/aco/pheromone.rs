@@ -3,12 +3,25 @@
 pub struct PheromoneMatrix {
     matrix: Vec<Vec<f64>>,
     size: usize,
+    // Symmetric trails are shared by both directions of an edge; asymmetric ones are directional
+    symmetric: bool,
 }
 
 impl PheromoneMatrix {
     pub fn new(size: usize, initial_pheromone: f64) -> Self {
         let matrix = vec![vec![initial_pheromone; size]; size];
-        PheromoneMatrix { matrix, size }
+        PheromoneMatrix { matrix, size, symmetric: true }
+    }
+
+    pub fn asymmetric(size: usize, initial_pheromone: f64) -> Self {
+        PheromoneMatrix {
+            symmetric: false,
+            ..Self::new(size, initial_pheromone)
+        }
+    }
+
+    pub fn is_symmetric(&self) -> bool {
+        self.symmetric
     }
 
     pub fn get(&self, i: usize, j: usize) -> f64 {
@@ -22,7 +35,9 @@ impl PheromoneMatrix {
     pub fn set(&mut self, i: usize, j: usize, value: f64) {
         if i < self.size && j < self.size {
             self.matrix[i][j] = value;
-            self.matrix[j][i] = value;
+            if self.symmetric {
+                self.matrix[j][i] = value;
+            }
         }
     }
 
@@ -35,17 +50,66 @@ impl PheromoneMatrix {
     }
 
     pub fn deposit(&mut self, route: &[usize], total_distance: f64) {
+        self.deposit_weighted(route, total_distance, 1.0);
+    }
+
+    pub fn deposit_weighted(&mut self, route: &[usize], total_distance: f64, weight: f64) {
         if route.len() < 2 || total_distance <= 0.0 {
             return;
         }
 
-        let pheromone_deposit = 1.0 / total_distance;
+        let pheromone_deposit = weight / total_distance;
         
         for i in 0..route.len() - 1 {
             let city1 = route[i];
             let city2 = route[i + 1];
             self.matrix[city1][city2] += pheromone_deposit;
-            self.matrix[city2][city1] += pheromone_deposit;
+            if self.symmetric {
+                self.matrix[city2][city1] += pheromone_deposit;
+            }
+        }
+    }
+
+    // ACS local update, applied as soon as an ant crosses the edge
+    pub fn local_update(&mut self, i: usize, j: usize, rate: f64, initial_pheromone: f64) {
+        if i < self.size && j < self.size && i != j {
+            let value = (1.0 - rate) * self.matrix[i][j] + rate * initial_pheromone;
+            self.set(i, j, value);
+        }
+    }
+
+    // ACS global update: evaporation and deposit restricted to the edges of `route`
+    pub fn global_update(&mut self, route: &[usize], total_distance: f64, rate: f64) {
+        if route.len() < 2 || total_distance <= 0.0 {
+            return;
+        }
+
+        for edge in route.windows(2) {
+            let value = (1.0 - rate) * self.matrix[edge[0]][edge[1]] + rate / total_distance;
+            self.set(edge[0], edge[1], value);
+        }
+    }
+
+    pub fn fill(&mut self, value: f64) {
+        for row in &mut self.matrix {
+            row.fill(value);
+        }
+    }
+
+    pub fn clamp(&mut self, min: f64, max: f64) {
+        for row in &mut self.matrix {
+            for value in row.iter_mut() {
+                *value = value.clamp(min, max);
+            }
+        }
+    }
+
+    // Pheromone trail smoothing: pull every trail towards `max` proportionally to its distance from it
+    pub fn smooth(&mut self, max: f64, delta: f64) {
+        for row in &mut self.matrix {
+            for value in row.iter_mut() {
+                *value += delta * (max - *value);
+            }
         }
     }
 
@@ -53,6 +117,29 @@ impl PheromoneMatrix {
         &self.matrix
     }
 
+    // Row-major copy of the matrix, the layout handed to the renderer and JS
+    pub fn to_flat(&self) -> Vec<f64> {
+        self.matrix.concat()
+    }
+
+    // Strongest and weakest trail between two distinct cities
+    pub fn max(&self) -> f64 {
+        self.edges().fold(0.0, f64::max)
+    }
+
+    pub fn min(&self) -> f64 {
+        self.edges().reduce(f64::min).unwrap_or(0.0)
+    }
+
+    fn edges(&self) -> impl Iterator<Item = f64> + '_ {
+        self.matrix.iter().enumerate().flat_map(|(i, row)| {
+            row.iter()
+                .enumerate()
+                .filter(move |&(j, _)| j != i)
+                .map(|(_, &value)| value)
+        })
+    }
+
     pub fn size(&self) -> usize {
         self.size
     }
@@ -96,4 +183,79 @@ mod tests {
         assert_eq!(matrix.get(1, 2), 1.1);
         assert_eq!(matrix.get(2, 0), 1.1);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_pheromone_clamp() {
+        let mut matrix = PheromoneMatrix::new(3, 1.0);
+        matrix.set(0, 1, 5.0);
+        matrix.set(1, 2, 0.01);
+        matrix.clamp(0.1, 2.0);
+
+        assert_eq!(matrix.get(0, 1), 2.0);
+        assert_eq!(matrix.get(2, 1), 0.1);
+        assert_eq!(matrix.get(0, 2), 1.0);
+    }
+
+    #[test]
+    fn test_pheromone_smooth() {
+        let mut matrix = PheromoneMatrix::new(3, 1.0);
+        matrix.smooth(3.0, 0.5);
+        assert_eq!(matrix.get(0, 1), 2.0);
+
+        matrix.smooth(3.0, 1.0);
+        assert_eq!(matrix.get(1, 2), 3.0);
+    }
+
+    #[test]
+    fn test_pheromone_local_update() {
+        let mut matrix = PheromoneMatrix::new(3, 1.0);
+        matrix.local_update(0, 1, 0.5, 0.2);
+        assert_eq!(matrix.get(0, 1), 0.6);
+        assert_eq!(matrix.get(1, 0), 0.6);
+        assert_eq!(matrix.get(1, 2), 1.0);
+    }
+
+    #[test]
+    fn test_pheromone_global_update() {
+        let mut matrix = PheromoneMatrix::new(4, 1.0);
+        matrix.global_update(&[0, 1, 2, 0], 10.0, 0.5);
+        assert!((matrix.get(0, 1) - 0.55).abs() < 1e-12);
+        assert!((matrix.get(2, 0) - 0.55).abs() < 1e-12);
+        assert_eq!(matrix.get(0, 3), 1.0);
+    }
+
+    #[test]
+    fn test_pheromone_deposit_weighted() {
+        let mut matrix = PheromoneMatrix::new(3, 1.0);
+        matrix.deposit_weighted(&[0, 1, 2, 0], 10.0, 3.0);
+        assert!((matrix.get(0, 1) - 1.3).abs() < 1e-12);
+        assert!((matrix.get(0, 2) - 1.3).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_pheromone_flat_and_range() {
+        let mut matrix = PheromoneMatrix::new(3, 1.0);
+        matrix.set(0, 2, 4.0);
+        matrix.set(1, 2, 0.5);
+        matrix.matrix[1][1] = 10.0;
+
+        assert_eq!(matrix.to_flat(), vec![1.0, 1.0, 4.0, 1.0, 10.0, 0.5, 4.0, 0.5, 1.0]);
+        assert_eq!(matrix.max(), 4.0);
+        assert_eq!(matrix.min(), 0.5);
+        assert_eq!(PheromoneMatrix::new(1, 1.0).min(), 0.0);
+    }
+
+    #[test]
+    fn test_asymmetric_pheromone_is_directional() {
+        let mut matrix = PheromoneMatrix::asymmetric(3, 1.0);
+        assert!(!matrix.is_symmetric());
+
+        matrix.deposit(&[0, 1, 2, 0], 10.0);
+        assert_eq!(matrix.get(0, 1), 1.1);
+        assert_eq!(matrix.get(1, 0), 1.0);
+
+        matrix.local_update(2, 1, 0.5, 0.0);
+        assert_eq!(matrix.get(2, 1), 0.5);
+        assert_eq!(matrix.get(1, 2), 1.1);
+    }
+}
@@ -0,0 +1,125 @@
+use crate::aco::ant::Ant;
+use crate::geometry::route::RouteMode;
+
+// Precedence constraints of the sequential ordering problem: (a, b) means city a has to be
+// visited before city b, e.g. a pickup before its drop-off
+#[derive(Debug, Clone, PartialEq)]
+pub struct Precedence {
+    predecessors: Vec<Vec<usize>>,
+}
+
+impl Precedence {
+    // None if a pair names a city that does not exist, relates a city to itself or the pairs form
+    // a cycle, since no tour could satisfy them then
+    pub fn new(num_cities: usize, pairs: &[(usize, usize)]) -> Option<Self> {
+        let mut predecessors = vec![Vec::new(); num_cities];
+        for &(before, after) in pairs {
+            if before >= num_cities || after >= num_cities || before == after {
+                return None;
+            }
+            if !predecessors[after].contains(&before) {
+                predecessors[after].push(before);
+            }
+        }
+        let precedence = Precedence { predecessors };
+        precedence.is_acyclic().then_some(precedence)
+    }
+
+    // Kahn's algorithm: every city can be placed once its predecessors are
+    fn is_acyclic(&self) -> bool {
+        let size = self.num_cities();
+        let mut remaining: Vec<usize> = self.predecessors.iter().map(Vec::len).collect();
+        let mut ready: Vec<usize> = (0..size).filter(|&city| remaining[city] == 0).collect();
+        let mut placed = 0;
+        while let Some(city) = ready.pop() {
+            placed += 1;
+            for (after, before) in self.predecessors.iter().enumerate() {
+                if before.contains(&city) {
+                    remaining[after] -= 1;
+                    if remaining[after] == 0 {
+                        ready.push(after);
+                    }
+                }
+            }
+        }
+        placed == size
+    }
+
+    pub fn num_cities(&self) -> usize {
+        self.predecessors.len()
+    }
+
+    pub fn predecessors(&self, city: usize) -> &[usize] {
+        &self.predecessors[city]
+    }
+
+    fn has_successors(&self, city: usize) -> bool {
+        self.predecessors.iter().any(|before| before.contains(&city))
+    }
+
+    // Cities that may start a tour
+    pub fn sources(&self) -> Vec<usize> {
+        (0..self.num_cities()).filter(|&city| self.predecessors[city].is_empty()).collect()
+    }
+
+    // A fixed start must not wait for anything and a fixed end must not be waited for
+    pub fn fits(&self, route_mode: RouteMode) -> bool {
+        route_mode.start().is_none_or(|start| self.predecessors[start].is_empty())
+            && route_mode.end().is_none_or(|end| !self.has_successors(end))
+    }
+
+    // Whether every predecessor of `city` is already on the ant's tour
+    pub fn is_ready(&self, ant: &Ant, city: usize) -> bool {
+        self.predecessors[city].iter().all(|&before| ant.is_visited(before))
+    }
+
+    // The (before, after) pairs a route breaks; a closed tour is read from its first city
+    pub fn violations(&self, route: &[usize]) -> Vec<(usize, usize)> {
+        let mut position = vec![usize::MAX; self.num_cities()];
+        for (i, &city) in route.iter().enumerate() {
+            position[city] = position[city].min(i);
+        }
+        (0..self.num_cities())
+            .flat_map(|after| self.predecessors[after].iter().map(move |&before| (before, after)))
+            .filter(|&(before, after)| position[before] > position[after])
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::{city::City, distance::DistanceMatrix};
+
+    #[test]
+    fn test_precedence_validation() {
+        assert!(Precedence::new(4, &[(0, 1), (1, 2), (0, 2)]).is_some());
+        assert!(Precedence::new(4, &[(0, 1), (1, 2), (2, 0)]).is_none());
+        assert!(Precedence::new(4, &[(0, 4)]).is_none());
+        assert!(Precedence::new(4, &[(3, 3)]).is_none());
+
+        let precedence = Precedence::new(4, &[(0, 1), (1, 2)]).unwrap();
+        assert_eq!(precedence.sources(), vec![0, 3]);
+        assert!(precedence.fits(RouteMode::FixedStartEnd { start: 0, end: 2 }));
+        assert!(!precedence.fits(RouteMode::FixedStart { start: 1 }));
+        assert!(!precedence.fits(RouteMode::FixedStartEnd { start: 3, end: 1 }));
+    }
+
+    #[test]
+    fn test_ready_cities() {
+        let distances = DistanceMatrix::from_cities(&(0..3).map(|i| City::new(i, i as f64, 0.0)).collect::<Vec<_>>());
+        let precedence = Precedence::new(3, &[(1, 2)]).unwrap();
+        let mut ant = Ant::new(0, 3);
+        assert!(precedence.is_ready(&ant, 1));
+        assert!(!precedence.is_ready(&ant, 2));
+        ant.move_to_city(1, &distances);
+        assert!(precedence.is_ready(&ant, 2));
+    }
+
+    #[test]
+    fn test_violations() {
+        let precedence = Precedence::new(4, &[(3, 1), (2, 1)]).unwrap();
+        assert!(precedence.violations(&[0, 3, 2, 1, 0]).is_empty());
+        assert_eq!(precedence.violations(&[0, 1, 2, 3, 0]), vec![(3, 1), (2, 1)]);
+    }
+}
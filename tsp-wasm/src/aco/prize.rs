@@ -0,0 +1,277 @@
+use crate::aco::{ant::Ant, choice::inverse_distance, problem::Problem};
+use crate::geometry::{city::City, distance::DistanceMatrix};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PrizeGoal {
+    // Orienteering: collect as much prize as possible on a tour no longer than the budget
+    Orienteering { budget: f64 },
+    // Prize-collecting TSP: minimise the tour length plus the prizes of the skipped cities
+    SkipPenalty,
+}
+
+impl PrizeGoal {
+    pub fn from_parts(name: &str, budget: Option<f64>) -> Option<PrizeGoal> {
+        match (name.to_ascii_lowercase().as_str(), budget) {
+            ("orienteering" | "op", Some(budget)) if budget > 0.0 => Some(PrizeGoal::Orienteering { budget }),
+            ("prize-collecting" | "pctsp" | "penalty", _) => Some(PrizeGoal::SkipPenalty),
+            _ => None,
+        }
+    }
+}
+
+// Tours that may skip cities: they start and end at `depot` and visit only the cities worth the
+// detour. The prizes come from the cities; a city without one is worth nothing.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PrizeCollecting {
+    pub depot: usize,
+    pub goal: PrizeGoal,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PrizeSummary {
+    pub collected: f64,
+    pub length: f64,
+    pub skipped: Vec<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PrizeCollectingTsp {
+    depot: usize,
+    goal: PrizeGoal,
+    prizes: Vec<f64>,
+    // Added to every prize in the heuristic, so prize-less cities can still lead to others
+    prize_floor: f64,
+    distances: DistanceMatrix,
+}
+
+impl PrizeCollectingTsp {
+    // None unless there is one city per distance matrix row and the depot is one of them
+    pub fn new(settings: PrizeCollecting, cities: &[City], distances: DistanceMatrix) -> Option<Self> {
+        if cities.len() != distances.size() || settings.depot >= cities.len() {
+            return None;
+        }
+        let prizes: Vec<f64> = cities.iter().map(|city| city.prize().unwrap_or(0.0)).collect();
+        let largest = prizes.iter().copied().fold(0.0, f64::max);
+        Some(PrizeCollectingTsp {
+            depot: settings.depot,
+            goal: settings.goal,
+            prizes,
+            prize_floor: if largest > 0.0 { 0.01 * largest } else { 1.0 },
+            distances,
+        })
+    }
+
+    fn prize(&self, city: usize) -> f64 {
+        if city == self.depot {
+            0.0
+        } else {
+            self.prizes[city]
+        }
+    }
+
+    pub fn prize_summary(&self, route: &[usize]) -> PrizeSummary {
+        let mut visited = vec![false; self.prizes.len()];
+        for &city in route {
+            visited[city] = true;
+        }
+        PrizeSummary {
+            collected: route.iter().filter(|&&city| city != self.depot).map(|&city| self.prize(city)).sum(),
+            length: self.distances.route_length(route),
+            skipped: (0..visited.len()).filter(|&city| !visited[city] && city != self.depot).collect(),
+        }
+    }
+}
+
+impl Problem for PrizeCollectingTsp {
+    fn size(&self) -> usize {
+        self.distances.size()
+    }
+
+    fn is_symmetric(&self) -> bool {
+        self.distances.is_symmetric()
+    }
+
+    // Prize per unit of distance
+    fn heuristic(&self, from: usize, to: usize) -> f64 {
+        (self.prize(to) + self.prize_floor) * inverse_distance(&self.distances, from, to)
+    }
+
+    fn new_ant(&self, _index: usize) -> Ant {
+        Ant::new(self.depot, self.size())
+    }
+
+    // The stop rule: a city is only eligible while the tour can still afford it (orienteering)
+    // or while its prize outweighs the detour it adds to the way home (prize-collecting)
+    fn weight(&self, ant: &Ant, city: usize) -> Option<f64> {
+        let from = ant.current_city();
+        let there_and_back = self.distances.get(from, city) + self.distances.get(city, self.depot);
+        let worth_it = match self.goal {
+            PrizeGoal::Orienteering { budget } => ant.total_distance() + there_and_back <= budget,
+            PrizeGoal::SkipPenalty => there_and_back - self.distances.get(from, self.depot) < self.prize(city),
+        };
+        worth_it.then_some(1.0)
+    }
+
+    fn step(&self, ant: &mut Ant, next: Option<usize>) {
+        match next {
+            Some(city) => ant.move_to_city(city, &self.distances),
+            None => ant.complete_tour(&self.distances),
+        }
+    }
+
+    fn finish(&self, ant: &mut Ant) {
+        let cost = self.cost(ant.route());
+        ant.replace_route(ant.route().clone(), cost);
+    }
+
+    // Orienteering minimises the prize left behind, with a fraction of the budget used as
+    // tie-breaker so that cheaper tours collecting the same prize win
+    fn cost(&self, solution: &[usize]) -> f64 {
+        let summary = self.prize_summary(solution);
+        let missed: f64 = summary.skipped.iter().map(|&city| self.prize(city)).sum();
+        match self.goal {
+            PrizeGoal::Orienteering { budget } => missed + summary.length / budget,
+            PrizeGoal::SkipPenalty => summary.length + missed,
+        }
+    }
+
+    fn length(&self, solution: &[usize]) -> f64 {
+        self.distances.route_length(solution)
+    }
+
+    fn supports_local_search(&self) -> bool {
+        self.is_symmetric()
+    }
+
+    fn depot(&self) -> Option<usize> {
+        Some(self.depot)
+    }
+
+    // Prize collected, tour length and skipped cities
+    fn summary(&self, solution: &[usize]) -> Value {
+        json!(self.prize_summary(solution))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aco::colony::{ACOParameters, Colony};
+    use crate::aco::local_search::LocalSearchMode;
+
+    // A near city worth little, a far one worth a lot and one that is not worth the way
+    fn cities() -> Vec<City> {
+        vec![
+            City::new(0, 0.0, 0.0),
+            City::new(1, 10.0, 0.0).with_prize(5.0),
+            City::new(2, 30.0, 0.0).with_prize(100.0),
+            City::new(3, 0.0, -50.0).with_prize(20.0),
+        ]
+    }
+
+    fn problem(goal: PrizeGoal) -> PrizeCollectingTsp {
+        let distances = DistanceMatrix::from_cities(&cities());
+        PrizeCollectingTsp::new(PrizeCollecting { depot: 0, goal }, &cities(), distances).unwrap()
+    }
+
+    #[test]
+    fn test_prize_goal_from_parts() {
+        assert_eq!(PrizeGoal::from_parts("orienteering", Some(50.0)), Some(PrizeGoal::Orienteering { budget: 50.0 }));
+        assert_eq!(PrizeGoal::from_parts("orienteering", None), None);
+        assert_eq!(PrizeGoal::from_parts("pctsp", None), Some(PrizeGoal::SkipPenalty));
+    }
+
+    #[test]
+    fn test_orienteering_stop_rule() {
+        let problem = problem(PrizeGoal::Orienteering { budget: 60.0 });
+        let mut ant = problem.new_ant(0);
+        assert_eq!(problem.weight(&ant, 2), Some(1.0));
+        // A round trip to city 3 alone is 100 long
+        assert_eq!(problem.weight(&ant, 3), None);
+
+        problem.step(&mut ant, Some(2));
+        assert_eq!(problem.weight(&ant, 1), Some(1.0));
+        problem.step(&mut ant, Some(1));
+        problem.step(&mut ant, None);
+        problem.finish(&mut ant);
+
+        let summary = problem.prize_summary(ant.route());
+        assert_eq!(summary.collected, 105.0);
+        assert_eq!(summary.skipped, vec![3]);
+        assert_eq!(ant.total_distance(), 20.0 + 60.0 / 60.0);
+    }
+
+    #[test]
+    fn test_prize_less_cities_stay_reachable() {
+        let problem = problem(PrizeGoal::SkipPenalty);
+        // The depot is worth nothing
+        assert!(problem.heuristic(1, 0) > 0.0);
+        assert!(problem.heuristic(0, 2) > problem.heuristic(0, 1));
+    }
+
+    #[test]
+    fn test_prize_collecting_cost() {
+        let problem = problem(PrizeGoal::SkipPenalty);
+        let ant = problem.new_ant(0);
+        // City 3 is a detour of 100 for a prize of 20
+        assert_eq!(problem.weight(&ant, 3), None);
+        assert_eq!(problem.weight(&ant, 2), Some(1.0));
+        assert_eq!(problem.cost(&[0, 1, 2, 0]), 60.0 + 20.0);
+        assert_eq!(problem.cost(&[0, 0]), 125.0);
+    }
+
+    #[test]
+    fn test_orienteering_colony() {
+        // Three small prizes along the way to the right or one larger prize far to the left
+        let cities = vec![
+            City::new(0, 0.0, 0.0),
+            City::new(1, 10.0, 0.0).with_prize(10.0),
+            City::new(2, 20.0, 0.0).with_prize(10.0),
+            City::new(3, 30.0, 0.0).with_prize(10.0),
+            City::new(4, -40.0, 0.0).with_prize(25.0),
+        ];
+        let params = ACOParameters {
+            num_ants: 5,
+            max_generations: 20,
+            seed: Some(4),
+            local_search: LocalSearchMode::AllAnts,
+            ..Default::default()
+        };
+        let mut colony = Colony::new(cities.clone(), params);
+        let goal = PrizeGoal::Orienteering { budget: 60.0 };
+        let distances = colony.distances().clone();
+        assert!(PrizeCollectingTsp::new(PrizeCollecting { depot: 5, goal }, &cities, distances.clone()).is_none());
+        let problem = PrizeCollectingTsp::new(PrizeCollecting { depot: 0, goal }, &cities, distances).unwrap();
+        assert!(colony.set_problem(Box::new(problem)));
+        while colony.run_iteration() {}
+
+        let summary = colony.best_summary();
+        assert_eq!(summary["collected"], 30.0);
+        assert_eq!(summary["length"], 60.0);
+        assert_eq!(summary["skipped"], json!([4]));
+        assert_eq!(colony.best_distance(), 25.0 + 1.0);
+        assert_eq!(colony.best_length(), 60.0);
+    }
+
+    #[test]
+    fn test_polish_keeps_depot_only_tour() {
+        // No prize is worth the way there and back
+        let cities = vec![
+            City::new(0, 0.0, 0.0),
+            City::new(1, 10.0, 0.0).with_prize(1.0),
+            City::new(2, 0.0, 10.0).with_prize(1.0),
+        ];
+        let params = ACOParameters { num_ants: 3, max_generations: 2, seed: Some(1), ..Default::default() };
+        let mut colony = Colony::new(cities.clone(), params);
+        let settings = PrizeCollecting { depot: 0, goal: PrizeGoal::SkipPenalty };
+        let problem = PrizeCollectingTsp::new(settings, &cities, colony.distances().clone()).unwrap();
+        assert!(colony.set_problem(Box::new(problem)));
+        while colony.run_iteration() {}
+
+        assert_eq!(colony.best_route(), Some(&vec![0, 0]));
+        assert_eq!(colony.polish_best_route(), 0.0);
+        assert_eq!(colony.best_route(), Some(&vec![0, 0]));
+    }
+}
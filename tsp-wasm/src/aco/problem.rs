@@ -0,0 +1,238 @@
+use crate::aco::{ant::Ant, choice::inverse_distance, precedence::Precedence};
+use crate::geometry::{distance::DistanceMatrix, route::RouteMode};
+use serde_json::{json, Value};
+use std::fmt::Debug;
+
+// What the colony solves. Solutions are sequences of components (cities for tours) built one
+// component at a time; trails and heuristic values belong to pairs of consecutive components.
+pub trait Problem: Debug {
+    // Number of solution components
+    fn size(&self) -> usize;
+
+    // Whether (i, j) and (j, i) share a trail
+    fn is_symmetric(&self) -> bool;
+
+    // Desirability eta of following `from` with `to`, before the beta exponent
+    fn heuristic(&self, from: usize, to: usize) -> f64;
+
+    // An ant with its starting component in place
+    fn new_ant(&self, index: usize) -> Ant;
+
+    // None if `component` may not come next in the ant's partial solution, otherwise a factor
+    // for its selection weight; only asked about components the ant has not visited yet
+    fn weight(&self, _ant: &Ant, _component: usize) -> Option<f64> {
+        Some(1.0)
+    }
+
+    // Adds the selected component, or reacts to the ant having no feasible component left
+    // (by completing the solution or by otherwise changing its state)
+    fn step(&self, ant: &mut Ant, next: Option<usize>);
+
+    // Turns a completed construction into the final solution
+    fn finish(&self, _ant: &mut Ant) {}
+
+    fn cost(&self, solution: &[usize]) -> f64;
+
+    // Length of the solution's routes; the cost unless that also weighs something else, e.g. the
+    // prizes left behind
+    fn length(&self, solution: &[usize]) -> f64 {
+        self.cost(solution)
+    }
+
+    // Whether the tour-based local search and polishing keep solutions valid
+    fn supports_local_search(&self) -> bool {
+        false
+    }
+
+    // Improves a solution with the problem's own moves when the tour moves do not apply;
+    // returns the cost saved
+    fn local_search(&self, _solution: &mut Vec<usize>) -> f64 {
+        0.0
+    }
+
+    // The shared start and end of the routes a solution is made of, e.g. the vehicles' depot
+    fn depot(&self) -> Option<usize> {
+        None
+    }
+
+    // Soft constraints the solution breaks, e.g. late customers; they outweigh the cost when
+    // comparing solutions
+    fn violations(&self, _solution: &[usize]) -> usize {
+        0
+    }
+
+    // What the problem has to report about a solution besides its cost, e.g. the vehicles'
+    // schedules; null if there is nothing to add
+    fn summary(&self, _solution: &[usize]) -> Value {
+        Value::Null
+    }
+}
+
+// The travelling salesman problem: closed tours, or open paths with optional fixed endpoints,
+// optionally with precedence constraints (sequential ordering problem)
+#[derive(Debug, Clone)]
+pub struct Tsp {
+    distances: DistanceMatrix,
+    route_mode: RouteMode,
+    precedence: Option<Precedence>,
+}
+
+impl Tsp {
+    pub fn new(distances: DistanceMatrix, route_mode: RouteMode) -> Self {
+        Tsp { distances, route_mode, precedence: None }
+    }
+
+    // None if the constraints do not cover the cities or contradict the fixed endpoints
+    pub fn with_precedence(mut self, precedence: Precedence) -> Option<Self> {
+        if precedence.num_cities() != self.size() || !precedence.fits(self.route_mode) {
+            return None;
+        }
+        self.precedence = Some(precedence);
+        Some(self)
+    }
+}
+
+impl Problem for Tsp {
+    fn size(&self) -> usize {
+        self.distances.size()
+    }
+
+    fn is_symmetric(&self) -> bool {
+        self.distances.is_symmetric()
+    }
+
+    fn heuristic(&self, from: usize, to: usize) -> f64 {
+        inverse_distance(&self.distances, from, to)
+    }
+
+    fn new_ant(&self, index: usize) -> Ant {
+        let size = self.size();
+        // Ants spread over the cities that nothing has to precede
+        let start = match &self.precedence {
+            Some(precedence) => {
+                let sources = precedence.sources();
+                sources[index % sources.len()]
+            }
+            None => index % size,
+        };
+        let mut ant = Ant::new(self.route_mode.start().unwrap_or(start), size);
+        if let Some(end) = self.route_mode.end() {
+            ant.reserve_city(end);
+        }
+        ant
+    }
+
+    fn weight(&self, ant: &Ant, city: usize) -> Option<f64> {
+        self.precedence.as_ref().is_none_or(|precedence| precedence.is_ready(ant, city)).then_some(1.0)
+    }
+
+    fn step(&self, ant: &mut Ant, next: Option<usize>) {
+        match next {
+            Some(city) => ant.move_to_city(city, &self.distances),
+            None if self.route_mode.is_closed() => ant.complete_tour(&self.distances),
+            None => ant.complete_path(self.route_mode.end(), &self.distances),
+        }
+    }
+
+    fn cost(&self, solution: &[usize]) -> f64 {
+        self.distances.route_length(solution)
+    }
+
+    // The moves reverse tour segments, which only keeps their length on symmetric instances and
+    // breaks precedence constraints
+    fn supports_local_search(&self) -> bool {
+        self.is_symmetric() && self.precedence.is_none()
+    }
+
+    // The constraints the tour breaks, as [before, after] pairs
+    fn summary(&self, solution: &[usize]) -> Value {
+        match &self.precedence {
+            Some(precedence) => json!({ "precedence_violations": precedence.violations(solution) }),
+            None => Value::Null,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aco::colony::{ACOParameters, Colony};
+    use crate::aco::local_search::LocalSearchMode;
+    use crate::geometry::city::City;
+
+    fn square() -> DistanceMatrix {
+        DistanceMatrix::from_cities(&[
+            City::new(0, 0.0, 0.0),
+            City::new(1, 10.0, 0.0),
+            City::new(2, 10.0, 10.0),
+            City::new(3, 0.0, 10.0),
+        ])
+    }
+
+    // Builds a solution by always taking the lowest feasible component
+    fn construct(problem: &dyn Problem, index: usize) -> Ant {
+        let mut ant = problem.new_ant(index);
+        while !ant.is_tour_complete() {
+            let next = (0..problem.size())
+                .find(|&component| !ant.is_visited(component) && problem.weight(&ant, component).is_some());
+            problem.step(&mut ant, next);
+        }
+        problem.finish(&mut ant);
+        ant
+    }
+
+    #[test]
+    fn test_tsp_closed_tour() {
+        let tsp = Tsp::new(square(), RouteMode::Closed);
+        let ant = construct(&tsp, 5);
+        assert_eq!(ant.route(), &vec![1, 0, 2, 3, 1]);
+        assert_eq!(tsp.cost(ant.route()), ant.total_distance());
+        assert!(tsp.supports_local_search());
+    }
+
+    #[test]
+    fn test_tsp_fixed_endpoints() {
+        let tsp = Tsp::new(square(), RouteMode::FixedStartEnd { start: 2, end: 0 });
+        let ant = construct(&tsp, 0);
+        assert_eq!(ant.route(), &vec![2, 1, 3, 0]);
+        assert_eq!(ant.total_distance(), 10.0 + 200f64.sqrt() + 10.0);
+    }
+
+    #[test]
+    fn test_tsp_with_precedence() {
+        // City 3 before city 1, so the lowest-first construction has to skip city 1 at first
+        let precedence = Precedence::new(4, &[(3, 1)]).unwrap();
+        let tsp = Tsp::new(square(), RouteMode::FixedStart { start: 0 }).with_precedence(precedence).unwrap();
+        let ant = construct(&tsp, 0);
+        assert_eq!(ant.route(), &vec![0, 2, 3, 1]);
+        assert!(!tsp.supports_local_search());
+
+        let backwards = Precedence::new(4, &[(1, 0)]).unwrap();
+        assert!(Tsp::new(square(), RouteMode::FixedStart { start: 0 }).with_precedence(backwards).is_none());
+    }
+
+    #[test]
+    fn test_sequential_ordering_colony() {
+        let cities: Vec<City> = (0..6).map(|i| City::new(i, 10.0 * i as f64, 0.0)).collect();
+        let route_mode = RouteMode::FixedStart { start: 0 };
+        let params = ACOParameters {
+            num_ants: 6,
+            max_generations: 30,
+            seed: Some(9),
+            route_mode,
+            local_search: LocalSearchMode::AllAnts,
+            ..Default::default()
+        };
+        let mut colony = Colony::new(cities, params);
+        // Cities further out have to be visited before those closer to the start
+        let precedence = Precedence::new(6, &[(4, 1), (5, 2)]).unwrap();
+        let tsp = Tsp::new(colony.distances().clone(), route_mode).with_precedence(precedence.clone()).unwrap();
+        assert!(colony.set_problem(Box::new(tsp)));
+        while colony.run_iteration() {}
+
+        let route = colony.best_route().unwrap();
+        assert!(precedence.violations(route).is_empty());
+        assert_eq!(colony.best_summary()["precedence_violations"], json!([]));
+        assert_eq!(colony.best_distance(), 90.0);
+    }
+}
@@ -0,0 +1,298 @@
+use crate::aco::{ant::Ant, problem::Problem};
+use serde_json::{json, Value};
+
+// The quadratic assignment problem: place n facilities on n locations so that the sum of
+// flow(i, j) * distance(location of i, location of j) is minimal.
+//
+// Components 0..n are the facilities and n..2n the locations. Ants alternate between them,
+// taking the facilities in a fixed order and choosing a free location for each, so a solution is
+// a path facility, location, facility, location, ... and the trail on (facility, location) is the
+// desirability of that assignment.
+#[derive(Debug, Clone)]
+pub struct Qap {
+    size: usize,
+    // Row-major n x n matrices
+    flow: Vec<f64>,
+    distance: Vec<f64>,
+    // Facilities by decreasing flow potential: the busiest ones are placed first
+    order: Vec<usize>,
+    flow_potential: Vec<f64>,
+    distance_potential: Vec<f64>,
+}
+
+impl Qap {
+    // None unless both matrices are square, of the same size (at least 2) and hold finite,
+    // non-negative entries
+    pub fn new(flow: Vec<f64>, distance: Vec<f64>) -> Option<Self> {
+        let size = (flow.len() as f64).sqrt() as usize;
+        let valid = size >= 2
+            && flow.len() == size * size
+            && distance.len() == flow.len()
+            && flow.iter().chain(&distance).all(|&value| value.is_finite() && value >= 0.0);
+        if !valid {
+            return None;
+        }
+
+        let potential = |matrix: &[f64], i: usize| (0..size).map(|j| matrix[i * size + j] + matrix[j * size + i]).sum();
+        let flow_potential: Vec<f64> = (0..size).map(|i| potential(&flow, i)).collect();
+        let distance_potential: Vec<f64> = (0..size).map(|i| potential(&distance, i)).collect();
+        let mut order: Vec<usize> = (0..size).collect();
+        order.sort_by(|&a, &b| flow_potential[b].total_cmp(&flow_potential[a]));
+
+        Some(Qap { size, flow, distance, order, flow_potential, distance_potential })
+    }
+
+    // Number of facilities (and locations)
+    pub fn num_facilities(&self) -> usize {
+        self.size
+    }
+
+    fn flow(&self, i: usize, j: usize) -> f64 {
+        self.flow[i * self.size + j]
+    }
+
+    fn distance(&self, a: usize, b: usize) -> f64 {
+        self.distance[a * self.size + b]
+    }
+
+    // Location of every facility in a solution path
+    pub fn assignment(&self, solution: &[usize]) -> Vec<usize> {
+        let mut assignment = vec![0; self.size];
+        for pair in solution.chunks_exact(2) {
+            assignment[pair[0]] = pair[1] - self.size;
+        }
+        assignment
+    }
+
+    // The solution path placing every facility at its location in `assignment`
+    pub fn route_for(&self, assignment: &[usize]) -> Vec<usize> {
+        self.order.iter().flat_map(|&facility| [facility, self.size + assignment[facility]]).collect()
+    }
+
+    pub fn assignment_cost(&self, assignment: &[usize]) -> f64 {
+        let mut cost = 0.0;
+        for i in 0..self.size {
+            for j in 0..self.size {
+                cost += self.flow(i, j) * self.distance(assignment[i], assignment[j]);
+            }
+        }
+        cost
+    }
+
+    // Change in cost from swapping the locations of facilities r and s; only the terms of pairs
+    // involving r or s change
+    fn swap_delta(&self, assignment: &[usize], r: usize, s: usize) -> f64 {
+        let moved = |k: usize| match k {
+            k if k == r => assignment[s],
+            k if k == s => assignment[r],
+            k => assignment[k],
+        };
+        let term = |i: usize, j: usize| {
+            self.flow(i, j) * (self.distance(moved(i), moved(j)) - self.distance(assignment[i], assignment[j]))
+        };
+        let mut delta = 0.0;
+        for k in 0..self.size {
+            delta += term(r, k) + term(s, k);
+            if k != r && k != s {
+                delta += term(k, r) + term(k, s);
+            }
+        }
+        delta
+    }
+
+    // Best-improvement 2-exchange: swaps the locations of two facilities as long as that lowers
+    // the cost; returns the cost saved
+    pub fn two_exchange(&self, assignment: &mut [usize]) -> f64 {
+        let mut total_gain = 0.0;
+        loop {
+            let mut best = (0.0, 0, 0);
+            for r in 0..self.size {
+                for s in r + 1..self.size {
+                    let delta = self.swap_delta(assignment, r, s);
+                    if delta < best.0 - 1e-9 {
+                        best = (delta, r, s);
+                    }
+                }
+            }
+            let (delta, r, s) = best;
+            if delta >= 0.0 {
+                return total_gain;
+            }
+            assignment.swap(r, s);
+            total_gain -= delta;
+        }
+    }
+}
+
+impl Problem for Qap {
+    fn size(&self) -> usize {
+        2 * self.size
+    }
+
+    fn is_symmetric(&self) -> bool {
+        false
+    }
+
+    // Busy facilities are drawn to locations close to all others (Maniezzo's AS-QAP heuristic);
+    // the step from a location to the next facility is fixed
+    fn heuristic(&self, from: usize, to: usize) -> f64 {
+        match (from < self.size, to < self.size) {
+            (true, false) => 1.0 / (self.flow_potential[from] * self.distance_potential[to - self.size]).max(1e-10),
+            (false, true) => 1.0,
+            _ => 0.0,
+        }
+    }
+
+    fn new_ant(&self, _index: usize) -> Ant {
+        Ant::new(self.order[0], self.size())
+    }
+
+    // A facility goes to any free location, a location is followed by the next facility in order
+    fn weight(&self, ant: &Ant, component: usize) -> Option<f64> {
+        let at_facility = ant.current_city() < self.size;
+        let eligible = if at_facility {
+            component >= self.size
+        } else {
+            component == self.order[ant.route().len() / 2]
+        };
+        eligible.then_some(1.0)
+    }
+
+    fn step(&self, ant: &mut Ant, next: Option<usize>) {
+        match next {
+            Some(component) => ant.visit(component),
+            None => ant.stop(),
+        }
+    }
+
+    fn finish(&self, ant: &mut Ant) {
+        let cost = self.cost(ant.route());
+        ant.replace_route(ant.route().clone(), cost);
+    }
+
+    fn cost(&self, solution: &[usize]) -> f64 {
+        self.assignment_cost(&self.assignment(solution))
+    }
+
+    fn local_search(&self, solution: &mut Vec<usize>) -> f64 {
+        let mut assignment = self.assignment(solution);
+        let gain = self.two_exchange(&mut assignment);
+        if gain > 0.0 {
+            *solution = self.route_for(&assignment);
+        }
+        gain
+    }
+
+    // The location of every facility
+    fn summary(&self, solution: &[usize]) -> Value {
+        json!({ "assignment": self.assignment(solution) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aco::colony::{ACOParameters, AcoVariant, Colony};
+    use crate::aco::local_search::LocalSearchMode;
+    use crate::geometry::{city::City, layout::circle_layout};
+
+    // Facilities 0 and 1 exchange a lot, location 2 is far from the others
+    fn qap() -> Qap {
+        let flow = vec![0.0, 5.0, 2.0, 5.0, 0.0, 3.0, 2.0, 3.0, 0.0];
+        let distance = vec![0.0, 8.0, 15.0, 8.0, 0.0, 13.0, 15.0, 13.0, 0.0];
+        Qap::new(flow, distance).unwrap()
+    }
+
+    fn construct(problem: &Qap) -> Ant {
+        let mut ant = problem.new_ant(0);
+        while !ant.is_tour_complete() {
+            let next = (0..problem.size())
+                .find(|&component| !ant.is_visited(component) && problem.weight(&ant, component).is_some());
+            problem.step(&mut ant, next);
+        }
+        problem.finish(&mut ant);
+        ant
+    }
+
+    #[test]
+    fn test_qap_validation() {
+        assert!(Qap::new(vec![0.0; 4], vec![0.0; 4]).is_some());
+        assert!(Qap::new(vec![0.0; 4], vec![0.0; 9]).is_none());
+        assert!(Qap::new(vec![0.0; 3], vec![0.0; 3]).is_none());
+        assert!(Qap::new(vec![0.0, -1.0, 0.0, 0.0], vec![0.0; 4]).is_none());
+    }
+
+    #[test]
+    fn test_construction_alternates() {
+        let qap = qap();
+        // Facility 1 has the largest flow potential and is placed first
+        let ant = construct(&qap);
+        assert_eq!(ant.route(), &vec![1, 3, 0, 4, 2, 5]);
+        assert_eq!(qap.assignment(ant.route()), vec![1, 0, 2]);
+        assert_eq!(ant.total_distance(), 2.0 * (5.0 * 8.0 + 2.0 * 13.0 + 3.0 * 15.0));
+        assert_eq!(qap.route_for(&[1, 0, 2]), ant.route().clone());
+    }
+
+    #[test]
+    fn test_two_exchange() {
+        let qap = qap();
+        let mut assignment = vec![2, 1, 0];
+        let start = qap.assignment_cost(&assignment);
+        for (r, s) in [(0, 1), (0, 2), (1, 2)] {
+            let mut swapped = assignment.clone();
+            swapped.swap(r, s);
+            let expected = qap.assignment_cost(&swapped) - start;
+            assert!((qap.swap_delta(&assignment, r, s) - expected).abs() < 1e-9);
+        }
+
+        let gain = qap.two_exchange(&mut assignment);
+        assert_eq!(start - gain, qap.assignment_cost(&assignment));
+        // The two busy facilities end up on the two close locations
+        assert_eq!(assignment[2], 2);
+    }
+
+    #[test]
+    fn test_quadratic_assignment_colony() {
+        let n: usize = 5;
+        // Irregular flows with an empty diagonal, locations on a line with uneven road factors
+        let flow: Vec<f64> = (0..n * n).map(|k| if k % (n + 1) == 0 { 0.0 } else { ((k * 7 + 3) % 10) as f64 }).collect();
+        let distance: Vec<f64> = (0..n * n).map(|k| (k / n).abs_diff(k % n) as f64 * ((k * 3) % 4 + 1) as f64).collect();
+        let qap = Qap::new(flow, distance).unwrap();
+
+        // Brute force over all 120 assignments
+        fn permutations(prefix: &mut Vec<usize>, n: usize, out: &mut Vec<Vec<usize>>) {
+            if prefix.len() == n {
+                out.push(prefix.clone());
+            }
+            for location in 0..n {
+                if !prefix.contains(&location) {
+                    prefix.push(location);
+                    permutations(prefix, n, out);
+                    prefix.pop();
+                }
+            }
+        }
+        let mut all = Vec::new();
+        permutations(&mut Vec::new(), n, &mut all);
+        let optimum = all.iter().map(|assignment| qap.assignment_cost(assignment)).fold(f64::INFINITY, f64::min);
+
+        let params = ACOParameters {
+            num_ants: 5,
+            max_generations: 30,
+            variant: AcoVariant::MaxMin,
+            seed: Some(3),
+            local_search: LocalSearchMode::IterationBest,
+            ..Default::default()
+        };
+        let cities = circle_layout(2 * n).iter().enumerate().map(|(i, &(x, y))| City::new(i as u32, x, y)).collect();
+        let mut colony = Colony::with_problem(cities, Box::new(qap.clone()), params);
+        assert!(!colony.is_symmetric());
+        while colony.run_iteration() {}
+
+        let route = colony.best_route().unwrap();
+        assert_eq!(route.len(), 2 * n);
+        assert_eq!(qap.cost(route), colony.best_distance());
+        assert_eq!(colony.best_distance(), optimum);
+        assert_eq!(colony.best_summary()["assignment"], json!(qap.assignment(route)));
+    }
+}
@@ -0,0 +1,459 @@
+use crate::aco::{ant::Ant, colony::{ACOParameters, AcoVariant}, pheromone::PheromoneMatrix};
+use std::fmt::Debug;
+
+// Everything a pheromone update rule may look at after a generation has been built
+pub struct UpdateContext<'a> {
+    pub ants: &'a [Ant],
+    pub best_route: Option<&'a [usize]>,
+    pub best_distance: f64,
+    pub improved: bool,
+    pub generation: usize,
+    pub num_cities: usize,
+}
+
+impl UpdateContext<'_> {
+    pub fn iteration_best(&self) -> Option<&Ant> {
+        self.ants
+            .iter()
+            .min_by(|a, b| a.total_distance().total_cmp(&b.total_distance()))
+    }
+
+    pub fn iteration_worst(&self) -> Option<&Ant> {
+        self.ants
+            .iter()
+            .max_by(|a, b| a.total_distance().total_cmp(&b.total_distance()))
+    }
+}
+
+pub trait PheromoneUpdateStrategy: Debug {
+    fn name(&self) -> &str;
+
+    fn update(&mut self, context: &UpdateContext, pheromone: &mut PheromoneMatrix);
+
+    fn pheromone_limits(&self) -> Option<(f64, f64)> {
+        None
+    }
+}
+
+#[derive(Debug)]
+pub struct AntSystemUpdate {
+    pub evaporation_rate: f64,
+}
+
+impl PheromoneUpdateStrategy for AntSystemUpdate {
+    fn name(&self) -> &str {
+        "as"
+    }
+
+    fn update(&mut self, context: &UpdateContext, pheromone: &mut PheromoneMatrix) {
+        pheromone.evaporate(self.evaporation_rate);
+        for ant in context.ants {
+            pheromone.deposit(ant.route(), ant.total_distance());
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ElitistUpdate {
+    pub evaporation_rate: f64,
+    pub elitist_weight: f64,
+}
+
+impl PheromoneUpdateStrategy for ElitistUpdate {
+    fn name(&self) -> &str {
+        "elitist"
+    }
+
+    fn update(&mut self, context: &UpdateContext, pheromone: &mut PheromoneMatrix) {
+        pheromone.evaporate(self.evaporation_rate);
+        for ant in context.ants {
+            pheromone.deposit(ant.route(), ant.total_distance());
+        }
+        if let Some(route) = context.best_route {
+            pheromone.deposit_weighted(route, context.best_distance, self.elitist_weight);
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct RankBasedUpdate {
+    pub evaporation_rate: f64,
+    pub rank_size: usize,
+}
+
+impl PheromoneUpdateStrategy for RankBasedUpdate {
+    fn name(&self) -> &str {
+        "rank"
+    }
+
+    fn update(&mut self, context: &UpdateContext, pheromone: &mut PheromoneMatrix) {
+        pheromone.evaporate(self.evaporation_rate);
+
+        let w = self.rank_size.max(1);
+        let mut ranked: Vec<&Ant> = context.ants.iter().collect();
+        ranked.sort_by(|a, b| a.total_distance().total_cmp(&b.total_distance()));
+
+        for (rank, ant) in ranked.iter().take(w - 1).enumerate() {
+            let weight = (w - 1 - rank) as f64;
+            pheromone.deposit_weighted(ant.route(), ant.total_distance(), weight);
+        }
+        if let Some(route) = context.best_route {
+            pheromone.deposit_weighted(route, context.best_distance, w as f64);
+        }
+    }
+}
+
+// Pheromone limits for MAX-MIN Ant System (Stützle & Hoos, 2000)
+pub fn mmas_limits(best_distance: f64, evaporation_rate: f64, p_best: f64, num_cities: usize) -> (f64, f64) {
+    let tau_max = 1.0 / (evaporation_rate * best_distance);
+    let n = num_cities as f64;
+    let p_dec = p_best.powf(1.0 / n);
+    let avg = (n / 2.0 - 1.0).max(1.0);
+    let tau_min = (tau_max * (1.0 - p_dec) / (avg * p_dec)).min(tau_max);
+    (tau_min, tau_max)
+}
+
+#[derive(Debug)]
+pub struct MaxMinUpdate {
+    pub evaporation_rate: f64,
+    pub p_best: f64,
+    pub global_best_interval: usize,
+    pub stagnation_limit: usize,
+    pub trail_smoothing: f64,
+    limits: Option<(f64, f64)>,
+    stagnation: usize,
+}
+
+impl MaxMinUpdate {
+    pub fn new(parameters: &ACOParameters) -> Self {
+        MaxMinUpdate {
+            evaporation_rate: parameters.evaporation_rate,
+            p_best: parameters.p_best,
+            global_best_interval: parameters.global_best_interval,
+            stagnation_limit: parameters.stagnation_limit,
+            trail_smoothing: parameters.trail_smoothing,
+            limits: None,
+            stagnation: 0,
+        }
+    }
+}
+
+impl PheromoneUpdateStrategy for MaxMinUpdate {
+    fn name(&self) -> &str {
+        "mmas"
+    }
+
+    fn update(&mut self, context: &UpdateContext, pheromone: &mut PheromoneMatrix) {
+        let (tau_min, tau_max) = mmas_limits(
+            context.best_distance,
+            self.evaporation_rate,
+            self.p_best,
+            context.num_cities,
+        );
+
+        // Trails start at tau_max once the first tour gives us an estimate of it
+        if self.limits.is_none() {
+            pheromone.fill(tau_max);
+        }
+        self.limits = Some((tau_min, tau_max));
+
+        if context.improved {
+            self.stagnation = 0;
+        } else {
+            self.stagnation += 1;
+        }
+
+        pheromone.evaporate(self.evaporation_rate);
+
+        let interval = self.global_best_interval;
+        let use_global_best = interval > 0 && (context.generation + 1).is_multiple_of(interval);
+        let depositor = if use_global_best {
+            context.best_route.map(|route| (route, context.best_distance))
+        } else {
+            context
+                .iteration_best()
+                .map(|ant| (ant.route().as_slice(), ant.total_distance()))
+        };
+        if let Some((route, distance)) = depositor {
+            pheromone.deposit(route, distance);
+        }
+
+        pheromone.clamp(tau_min, tau_max);
+
+        if self.stagnation_limit > 0 && self.stagnation >= self.stagnation_limit {
+            pheromone.smooth(tau_max, self.trail_smoothing);
+            self.stagnation = 0;
+        }
+    }
+
+    fn pheromone_limits(&self) -> Option<(f64, f64)> {
+        self.limits
+    }
+}
+
+// ACS global update: only the best-so-far tour evaporates and receives pheromone
+#[derive(Debug)]
+pub struct AcsGlobalUpdate {
+    pub evaporation_rate: f64,
+}
+
+impl PheromoneUpdateStrategy for AcsGlobalUpdate {
+    fn name(&self) -> &str {
+        "acs"
+    }
+
+    fn update(&mut self, context: &UpdateContext, pheromone: &mut PheromoneMatrix) {
+        if let Some(route) = context.best_route {
+            pheromone.global_update(route, context.best_distance, self.evaporation_rate);
+        }
+    }
+}
+
+// Best-Worst Ant System (Cordón et al., 2000): the best-so-far tour deposits and the
+// edges of the iteration-worst tour that are not part of it are evaporated a second time
+#[derive(Debug)]
+pub struct BestWorstUpdate {
+    pub evaporation_rate: f64,
+}
+
+impl PheromoneUpdateStrategy for BestWorstUpdate {
+    fn name(&self) -> &str {
+        "best-worst"
+    }
+
+    fn update(&mut self, context: &UpdateContext, pheromone: &mut PheromoneMatrix) {
+        pheromone.evaporate(self.evaporation_rate);
+
+        let Some(best_route) = context.best_route else {
+            return;
+        };
+        pheromone.deposit(best_route, context.best_distance);
+
+        if let Some(worst) = context.iteration_worst() {
+            let mut best_edges = vec![false; context.num_cities * context.num_cities];
+            for edge in best_route.windows(2) {
+                best_edges[edge[0] * context.num_cities + edge[1]] = true;
+                best_edges[edge[1] * context.num_cities + edge[0]] = true;
+            }
+            for edge in worst.route().windows(2) {
+                if !best_edges[edge[0] * context.num_cities + edge[1]] {
+                    let value = pheromone.get(edge[0], edge[1]) * (1.0 - self.evaporation_rate);
+                    pheromone.set(edge[0], edge[1], value);
+                }
+            }
+        }
+    }
+}
+
+// Hyper-Cube framework (Blum & Dorigo, 2004): deposits are normalised by the quality of
+// the whole generation, which keeps every trail inside [0, 1] regardless of tour length
+#[derive(Debug)]
+pub struct HyperCubeUpdate {
+    pub evaporation_rate: f64,
+}
+
+impl PheromoneUpdateStrategy for HyperCubeUpdate {
+    fn name(&self) -> &str {
+        "hyper-cube"
+    }
+
+    fn update(&mut self, context: &UpdateContext, pheromone: &mut PheromoneMatrix) {
+        let total_quality: f64 = context
+            .ants
+            .iter()
+            .filter(|ant| ant.total_distance() > 0.0)
+            .map(|ant| 1.0 / ant.total_distance())
+            .sum();
+
+        pheromone.evaporate(self.evaporation_rate);
+        if total_quality <= 0.0 {
+            return;
+        }
+
+        for ant in context.ants {
+            // deposit_weighted divides by the tour length, so this adds rho * (1/L_k) / sum(1/L_j)
+            let weight = self.evaporation_rate / total_quality;
+            pheromone.deposit_weighted(ant.route(), ant.total_distance(), weight);
+        }
+    }
+}
+
+pub type StrategyFactory = fn(&ACOParameters) -> Box<dyn PheromoneUpdateStrategy>;
+
+// Name -> factory lookup used to select update rules by string (e.g. from JS)
+#[derive(Debug, Clone)]
+pub struct StrategyRegistry {
+    factories: Vec<(String, StrategyFactory)>,
+}
+
+impl StrategyRegistry {
+    pub fn new() -> Self {
+        StrategyRegistry { factories: Vec::new() }
+    }
+
+    pub fn with_builtins() -> Self {
+        let mut registry = StrategyRegistry::new();
+        registry.register("as", |p| Box::new(AntSystemUpdate { evaporation_rate: p.evaporation_rate }));
+        registry.register("elitist", |p| {
+            Box::new(ElitistUpdate {
+                evaporation_rate: p.evaporation_rate,
+                elitist_weight: p.elitist_weight,
+            })
+        });
+        registry.register("rank", |p| {
+            Box::new(RankBasedUpdate {
+                evaporation_rate: p.evaporation_rate,
+                rank_size: p.rank_size,
+            })
+        });
+        registry.register("mmas", |p| Box::new(MaxMinUpdate::new(p)));
+        registry.register("acs", |p| Box::new(AcsGlobalUpdate { evaporation_rate: p.evaporation_rate }));
+        registry.register("best-worst", |p| Box::new(BestWorstUpdate { evaporation_rate: p.evaporation_rate }));
+        registry.register("hyper-cube", |p| Box::new(HyperCubeUpdate { evaporation_rate: p.evaporation_rate }));
+        registry
+    }
+
+    // Registering an existing name replaces its factory
+    pub fn register(&mut self, name: &str, factory: StrategyFactory) {
+        let name = name.to_ascii_lowercase();
+        if let Some(entry) = self.factories.iter_mut().find(|(n, _)| *n == name) {
+            entry.1 = factory;
+        } else {
+            self.factories.push((name, factory));
+        }
+    }
+
+    pub fn create(&self, name: &str, parameters: &ACOParameters) -> Option<Box<dyn PheromoneUpdateStrategy>> {
+        let name = name.to_ascii_lowercase();
+        self.factories
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, factory)| factory(parameters))
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.factories.iter().map(|(name, _)| name.clone()).collect()
+    }
+}
+
+impl Default for StrategyRegistry {
+    fn default() -> Self {
+        StrategyRegistry::with_builtins()
+    }
+}
+
+pub fn default_strategy(parameters: &ACOParameters) -> Box<dyn PheromoneUpdateStrategy> {
+    let name = match parameters.variant {
+        AcoVariant::AntSystem => "as",
+        AcoVariant::MaxMin => "mmas",
+        AcoVariant::AntColonySystem => "acs",
+        AcoVariant::Elitist => "elitist",
+        AcoVariant::RankBased => "rank",
+    };
+    StrategyRegistry::with_builtins()
+        .create(name, parameters)
+        .expect("built-in strategy")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::{city::City, distance::DistanceMatrix};
+
+    fn context<'a>(ants: &'a [Ant], best_route: Option<&'a Vec<usize>>, best_distance: f64) -> UpdateContext<'a> {
+        UpdateContext {
+            ants,
+            best_route: best_route.map(|route| route.as_slice()),
+            best_distance,
+            improved: true,
+            generation: 0,
+            num_cities: 4,
+        }
+    }
+
+    #[test]
+    fn test_mmas_limits() {
+        let (tau_min, tau_max) = mmas_limits(100.0, 0.1, 0.05, 50);
+        assert!((tau_max - 0.1).abs() < 1e-12);
+        assert!(tau_min > 0.0 && tau_min < tau_max);
+    }
+
+    #[test]
+    fn test_registry_builtins() {
+        let registry = StrategyRegistry::with_builtins();
+        let params = ACOParameters::default();
+
+        for name in ["as", "elitist", "rank", "mmas", "acs", "best-worst", "hyper-cube"] {
+            assert_eq!(registry.create(name, &params).unwrap().name(), name);
+        }
+        assert!(registry.create("unknown", &params).is_none());
+    }
+
+    #[test]
+    fn test_registry_register_custom() {
+        #[derive(Debug)]
+        struct NoUpdate;
+
+        impl PheromoneUpdateStrategy for NoUpdate {
+            fn name(&self) -> &str {
+                "none"
+            }
+
+            fn update(&mut self, _context: &UpdateContext, _pheromone: &mut PheromoneMatrix) {}
+        }
+
+        let mut registry = StrategyRegistry::with_builtins();
+        registry.register("none", |_| Box::new(NoUpdate));
+        assert!(registry.names().contains(&"none".to_string()));
+        assert_eq!(registry.create("NONE", &ACOParameters::default()).unwrap().name(), "none");
+    }
+
+    fn tour(route: &[usize]) -> Ant {
+        let distances = DistanceMatrix::from_cities(&[
+            City::new(0, 0.0, 0.0),
+            City::new(1, 10.0, 0.0),
+            City::new(2, 10.0, 10.0),
+            City::new(3, 0.0, 10.0),
+        ]);
+        let mut ant = Ant::new(route[0], distances.size());
+        for &city in &route[1..] {
+            ant.move_to_city(city, &distances);
+        }
+        ant.complete_tour(&distances);
+        ant
+    }
+
+    #[test]
+    fn test_best_worst_penalises_worst_edges() {
+        let mut pheromone = PheromoneMatrix::new(4, 1.0);
+        let best = tour(&[0, 1, 2, 3]);
+        let worst = tour(&[0, 2, 1, 3]);
+        let ants = vec![best.clone(), worst];
+        let mut strategy = BestWorstUpdate { evaporation_rate: 0.5 };
+
+        strategy.update(
+            &context(&ants, Some(best.route()), best.total_distance()),
+            &mut pheromone,
+        );
+
+        // 0-2 is only in the worst tour, so it evaporates twice
+        assert!((pheromone.get(0, 2) - 0.25).abs() < 1e-12);
+        assert!((pheromone.get(0, 1) - (0.5 + 1.0 / 40.0)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_hyper_cube_stays_in_unit_interval() {
+        let mut pheromone = PheromoneMatrix::new(4, 0.5);
+        let ants = vec![tour(&[0, 1, 2, 3]), tour(&[0, 2, 1, 3]), tour(&[0, 1, 3, 2])];
+        let mut strategy = HyperCubeUpdate { evaporation_rate: 0.3 };
+
+        for _ in 0..50 {
+            strategy.update(&context(&ants, Some(ants[0].route()), 40.0), &mut pheromone);
+        }
+        for row in pheromone.get_matrix() {
+            for &value in row {
+                assert!((0.0..=1.0 + 1e-12).contains(&value));
+            }
+        }
+    }
+}
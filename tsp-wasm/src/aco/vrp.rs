@@ -0,0 +1,537 @@
+use crate::aco::{ant::Ant, choice::inverse_distance, problem::Problem};
+use crate::geometry::{distance::DistanceMatrix, neighbors::NeighborLists};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+// Capacitated vehicle routing: every customer's demand is delivered from the depot by vehicles
+// of limited capacity. Solutions are giant tours such as [0, 3, 1, 0, 2, 4, 0] in which each
+// visit to the depot closes one vehicle route.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VehicleRouting {
+    depot: usize,
+    demands: Vec<f64>,
+    capacity: f64,
+    time_windows: Option<TimeWindows>,
+}
+
+// Customer time windows (VRPTW). Travel times equal distances; service at a city may start no
+// earlier than its ready time and no later than its due time, so early vehicles wait. Vehicles
+// leave the depot at time 0 and must be back before the depot's due time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimeWindows {
+    ready: Vec<f64>,
+    due: Vec<f64>,
+    service: Vec<f64>,
+}
+
+impl TimeWindows {
+    // None unless all three lists have the same length, every window is non-empty and every
+    // service time non-negative; an infinite due time leaves a city unconstrained
+    pub fn new(ready: Vec<f64>, due: Vec<f64>, service: Vec<f64>) -> Option<Self> {
+        let valid = ready.len() == due.len()
+            && ready.len() == service.len()
+            && ready.iter().zip(&due).all(|(&ready, &due)| ready.is_finite() && ready <= due)
+            && service.iter().all(|&service| service.is_finite() && service >= 0.0);
+        valid.then_some(TimeWindows { ready, due, service })
+    }
+
+    pub fn ready(&self, city: usize) -> f64 {
+        self.ready[city]
+    }
+
+    pub fn due(&self, city: usize) -> f64 {
+        self.due[city]
+    }
+
+    pub fn service(&self, city: usize) -> f64 {
+        self.service[city]
+    }
+
+    pub fn len(&self) -> usize {
+        self.ready.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ready.is_empty()
+    }
+}
+
+// One stop of a vehicle route; lateness is how far the arrival missed the due time
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Visit {
+    pub city: usize,
+    pub arrival: f64,
+    pub start: f64,
+    pub departure: f64,
+    pub lateness: f64,
+}
+
+impl VehicleRouting {
+    // None unless the depot exists and every demand is non-negative and fits into one vehicle
+    pub fn new(depot: usize, demands: Vec<f64>, capacity: f64) -> Option<Self> {
+        let valid = depot < demands.len()
+            && capacity > 0.0
+            && demands.iter().all(|&demand| demand >= 0.0 && demand <= capacity);
+        valid.then_some(VehicleRouting { depot, demands, capacity, time_windows: None })
+    }
+
+    // Adds customer time windows; None if they do not cover exactly the routing's cities
+    pub fn with_time_windows(mut self, time_windows: TimeWindows) -> Option<Self> {
+        if time_windows.len() != self.num_cities() {
+            return None;
+        }
+        self.time_windows = Some(time_windows);
+        Some(self)
+    }
+
+    pub fn time_windows(&self) -> Option<&TimeWindows> {
+        self.time_windows.as_ref()
+    }
+
+    pub fn depot(&self) -> usize {
+        self.depot
+    }
+
+    pub fn capacity(&self) -> f64 {
+        self.capacity
+    }
+
+    pub fn demand(&self, city: usize) -> f64 {
+        if city == self.depot {
+            0.0
+        } else {
+            self.demands[city]
+        }
+    }
+
+    pub fn num_cities(&self) -> usize {
+        self.demands.len()
+    }
+
+    // Whether a vehicle already carrying `load` can also serve `city`
+    pub fn fits(&self, load: f64, city: usize) -> bool {
+        load + self.demand(city) <= self.capacity
+    }
+
+    // Time at which a vehicle leaving `from` at `time` is done serving `city`
+    pub fn departure(&self, time: f64, from: usize, city: usize, distances: &DistanceMatrix) -> f64 {
+        let arrival = time + distances.get(from, city);
+        match &self.time_windows {
+            Some(windows) => arrival.max(windows.ready(city)) + windows.service(city),
+            None => arrival,
+        }
+    }
+
+    // Whether a vehicle leaving `from` at `time` reaches `city` within its window and can still
+    // get back to the depot in time afterwards
+    pub fn reachable(&self, time: f64, from: usize, city: usize, distances: &DistanceMatrix) -> bool {
+        let Some(windows) = &self.time_windows else {
+            return true;
+        };
+        let arrival = time + distances.get(from, city);
+        let back = self.departure(time, from, city, distances) + distances.get(city, self.depot);
+        arrival <= windows.due(city) && back <= windows.due(self.depot)
+    }
+
+    // Extra weight for customers whose window closes soon or that can be served without waiting:
+    // (1 / (1 + waiting + slack))^weight, 1 without time windows
+    pub fn urgency(&self, time: f64, from: usize, city: usize, distances: &DistanceMatrix, weight: f64) -> f64 {
+        let Some(windows) = &self.time_windows else {
+            return 1.0;
+        };
+        if weight == 0.0 {
+            return 1.0;
+        }
+        let arrival = time + distances.get(from, city);
+        let waiting = (windows.ready(city) - arrival).max(0.0);
+        let slack = (windows.due(city) - arrival.max(windows.ready(city))).max(0.0);
+        if slack.is_infinite() {
+            return 1.0;
+        }
+        (1.0 + waiting + slack).powf(-weight)
+    }
+
+    // Splits a giant tour into its vehicle routes, each starting and ending at the depot
+    pub fn split_routes(&self, route: &[usize]) -> Vec<Vec<usize>> {
+        split_at_depot(route, self.depot)
+    }
+
+    pub fn route_load(&self, route: &[usize]) -> f64 {
+        route.iter().map(|&city| self.demand(city)).sum()
+    }
+
+    // Arrival, service and departure times along every vehicle route of a giant tour
+    pub fn schedule(&self, route: &[usize], distances: &DistanceMatrix) -> Vec<Vec<Visit>> {
+        self.split_routes(route)
+            .iter()
+            .map(|vehicle_route| {
+                let mut visits = vec![Visit { city: self.depot, arrival: 0.0, start: 0.0, departure: 0.0, lateness: 0.0 }];
+                for pair in vehicle_route.windows(2) {
+                    let (from, city) = (pair[0], pair[1]);
+                    let time = visits.last().map_or(0.0, |visit| visit.departure);
+                    let arrival = time + distances.get(from, city);
+                    // The vehicle is done once it is back at the depot
+                    let departure =
+                        if city == self.depot { arrival } else { self.departure(time, from, city, distances) };
+                    let start = match &self.time_windows {
+                        Some(windows) if city != self.depot => arrival.max(windows.ready(city)),
+                        _ => arrival,
+                    };
+                    let lateness = (arrival - self.due(city)).max(0.0);
+                    visits.push(Visit { city, arrival, start, departure, lateness });
+                }
+                visits
+            })
+            .collect()
+    }
+
+    // Number of visits in a giant tour that arrive after their due time
+    pub fn time_window_violations(&self, route: &[usize], distances: &DistanceMatrix) -> usize {
+        if self.time_windows.is_none() {
+            return 0;
+        }
+        self.schedule(route, distances).iter().flatten().filter(|visit| visit.lateness > 0.0).count()
+    }
+
+    fn due(&self, city: usize) -> f64 {
+        self.time_windows.as_ref().map_or(f64::INFINITY, |windows| windows.due(city))
+    }
+
+    // Improves each vehicle route on its own; returns the distance saved
+    pub fn improve_routes(
+        &self,
+        route: &mut Vec<usize>,
+        distances: &DistanceMatrix,
+        num_neighbors: usize,
+        search: impl FnMut(&mut Vec<usize>, &DistanceMatrix, &NeighborLists) -> f64,
+    ) -> f64 {
+        improve_each_route(route, self.depot, distances, num_neighbors, search)
+    }
+}
+
+// Vehicle routing as a colony problem: ants start at the depot and only consider customers that
+// fit into the vehicle and can be reached in time; when there are none the vehicle returns
+#[derive(Debug, Clone)]
+pub struct VehicleRoutingProblem {
+    routing: VehicleRouting,
+    distances: DistanceMatrix,
+    urgency_weight: f64,
+}
+
+impl VehicleRoutingProblem {
+    // None if the routing data does not match the distances
+    pub fn new(routing: VehicleRouting, distances: DistanceMatrix, urgency_weight: f64) -> Option<Self> {
+        (routing.num_cities() == distances.size()).then_some(VehicleRoutingProblem { routing, distances, urgency_weight })
+    }
+
+    // A fresh vehicle that cannot reach any remaining customer in time
+    fn stranded(&self, ant: &Ant) -> bool {
+        let depot = self.routing.depot;
+        ant.current_city() == depot
+            && ant.load() == 0.0
+            && !(0..self.size())
+                .any(|city| !ant.is_visited(city) && self.routing.reachable(ant.time(), depot, city, &self.distances))
+    }
+}
+
+impl Problem for VehicleRoutingProblem {
+    fn size(&self) -> usize {
+        self.distances.size()
+    }
+
+    fn is_symmetric(&self) -> bool {
+        self.distances.is_symmetric()
+    }
+
+    fn heuristic(&self, from: usize, to: usize) -> f64 {
+        inverse_distance(&self.distances, from, to)
+    }
+
+    fn new_ant(&self, _index: usize) -> Ant {
+        Ant::new(self.routing.depot, self.size())
+    }
+
+    fn weight(&self, ant: &Ant, city: usize) -> Option<f64> {
+        let (from, time) = (ant.current_city(), ant.time());
+        if !self.routing.fits(ant.load(), city) {
+            return None;
+        }
+        if self.routing.reachable(time, from, city, &self.distances) {
+            return Some(self.routing.urgency(time, from, city, &self.distances, self.urgency_weight));
+        }
+        // Customers that not even a fresh vehicle reaches in time are still served, late
+        self.stranded(ant).then_some(1.0)
+    }
+
+    fn step(&self, ant: &mut Ant, next: Option<usize>) {
+        match next {
+            Some(city) => {
+                ant.set_time(self.routing.departure(ant.time(), ant.current_city(), city, &self.distances));
+                ant.move_to_city(city, &self.distances);
+                ant.add_load(self.routing.demand(city));
+            }
+            // Nothing fits into the vehicle any more: it returns and the next one sets out
+            None => {
+                ant.return_to_depot(self.routing.depot, &self.distances);
+                if !ant.has_unvisited() {
+                    ant.complete_path(None, &self.distances);
+                }
+            }
+        }
+    }
+
+    fn cost(&self, solution: &[usize]) -> f64 {
+        self.distances.route_length(solution)
+    }
+
+    // The moves ignore arrival times, so they are only safe without time windows
+    fn supports_local_search(&self) -> bool {
+        self.is_symmetric() && self.routing.time_windows.is_none()
+    }
+
+    fn depot(&self) -> Option<usize> {
+        Some(self.routing.depot)
+    }
+
+    fn violations(&self, solution: &[usize]) -> usize {
+        self.routing.time_window_violations(solution, &self.distances)
+    }
+
+    fn summary(&self, solution: &[usize]) -> Value {
+        json!({
+            "schedules": self.routing.schedule(solution, &self.distances),
+            "time_window_violations": self.violations(solution),
+        })
+    }
+}
+
+// Splits a giant tour such as [0, 3, 1, 0, 2, 4, 0] at every visit to the depot
+pub fn split_at_depot(route: &[usize], depot: usize) -> Vec<Vec<usize>> {
+    let mut routes = Vec::new();
+    let mut current = vec![depot];
+    for &city in route.iter().skip_while(|&&city| city == depot) {
+        if city == depot {
+            if current.len() > 1 {
+                current.push(depot);
+                routes.push(std::mem::replace(&mut current, vec![depot]));
+            }
+        } else {
+            current.push(city);
+        }
+    }
+    if current.len() > 1 {
+        current.push(depot);
+        routes.push(current);
+    }
+    routes
+}
+
+// Improves each route of a giant tour on its own with a tour operator; the operators expect every
+// city of the matrix to be on the tour, so each route gets its own small matrix.
+// Returns the distance saved
+pub fn improve_each_route(
+    route: &mut Vec<usize>,
+    depot: usize,
+    distances: &DistanceMatrix,
+    num_neighbors: usize,
+    mut search: impl FnMut(&mut Vec<usize>, &DistanceMatrix, &NeighborLists) -> f64,
+) -> f64 {
+    let mut total_gain = 0.0;
+    let mut giant_tour = vec![depot];
+    for single_route in split_at_depot(route, depot) {
+        let cities = &single_route[..single_route.len() - 1];
+        let local = DistanceMatrix::from_fn(cities.len(), |i, j| distances.get(cities[i], cities[j]));
+        let neighbors = NeighborLists::new(&local, num_neighbors);
+        let mut local_route: Vec<usize> = (0..cities.len()).chain(std::iter::once(0)).collect();
+
+        total_gain += search(&mut local_route, &local, &neighbors);
+        giant_tour.extend(local_route[1..].iter().map(|&i| cities[i]));
+    }
+    *route = giant_tour;
+    total_gain
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aco::colony::{ACOParameters, Colony};
+    use crate::aco::local_search::{improve, LocalSearchMode, LocalSearchOperator};
+    use crate::geometry::city::City;
+
+    #[test]
+    fn test_vehicle_routing_validation() {
+        assert!(VehicleRouting::new(0, vec![0.0, 3.0, 4.0], 5.0).is_some());
+        assert!(VehicleRouting::new(3, vec![0.0, 3.0, 4.0], 5.0).is_none());
+        assert!(VehicleRouting::new(0, vec![0.0, 3.0, 6.0], 5.0).is_none());
+
+        let routing = VehicleRouting::new(1, vec![2.0, 9.0, 4.0], 10.0).unwrap();
+        assert_eq!(routing.demand(1), 0.0);
+        assert!(routing.fits(6.0, 2));
+        assert!(!routing.fits(6.5, 2));
+    }
+
+    #[test]
+    fn test_split_routes() {
+        let routing = VehicleRouting::new(0, vec![0.0, 1.0, 1.0, 1.0, 1.0], 2.0).unwrap();
+        let routes = routing.split_routes(&[0, 3, 1, 0, 2, 4, 0]);
+        assert_eq!(routes, vec![vec![0, 3, 1, 0], vec![0, 2, 4, 0]]);
+        assert_eq!(routing.route_load(&routes[0]), 2.0);
+        assert_eq!(routing.split_routes(&[0, 0]), Vec::<Vec<usize>>::new());
+    }
+
+    fn line_with_windows(due: Vec<f64>) -> (VehicleRouting, DistanceMatrix) {
+        let cities: Vec<City> = (0..3).map(|i| City::new(i, 10.0 * i as f64, 0.0)).collect();
+        let windows = TimeWindows::new(vec![0.0, 0.0, 30.0], due, vec![5.0, 5.0, 5.0]).unwrap();
+        let routing = VehicleRouting::new(0, vec![0.0, 1.0, 1.0], 2.0).unwrap().with_time_windows(windows).unwrap();
+        (routing, DistanceMatrix::from_cities(&cities))
+    }
+
+    #[test]
+    fn test_time_window_validation() {
+        assert!(TimeWindows::new(vec![0.0, 5.0], vec![10.0, f64::INFINITY], vec![0.0, 1.0]).is_some());
+        assert!(TimeWindows::new(vec![0.0, 5.0], vec![10.0, 4.0], vec![0.0, 1.0]).is_none());
+        assert!(TimeWindows::new(vec![0.0], vec![10.0], vec![-1.0]).is_none());
+
+        let windows = TimeWindows::new(vec![0.0; 2], vec![10.0; 2], vec![0.0; 2]).unwrap();
+        assert!(VehicleRouting::new(0, vec![0.0, 1.0, 1.0], 2.0).unwrap().with_time_windows(windows).is_none());
+    }
+
+    #[test]
+    fn test_reachable_and_urgency() {
+        let (routing, distances) = line_with_windows(vec![100.0, 15.0, 50.0]);
+        assert!(routing.reachable(0.0, 0, 1, &distances));
+        assert!(!routing.reachable(10.0, 0, 1, &distances));
+        // Waiting at city 2 until 30 and serving it still gets the vehicle back by 55
+        assert_eq!(routing.departure(0.0, 0, 2, &distances), 35.0);
+        assert!(routing.reachable(0.0, 0, 2, &distances));
+        let (early_depot, _) = line_with_windows(vec![40.0, 15.0, 50.0]);
+        assert!(!early_depot.reachable(0.0, 0, 2, &distances));
+
+        // City 1 can be served right away and closes soon, city 2 means waiting
+        assert!(routing.urgency(0.0, 0, 1, &distances, 1.0) > routing.urgency(0.0, 0, 2, &distances, 1.0));
+        assert_eq!(routing.urgency(0.0, 0, 2, &distances, 0.0), 1.0);
+    }
+
+    #[test]
+    fn test_schedule_reports_lateness() {
+        let (routing, distances) = line_with_windows(vec![100.0, 15.0, 50.0]);
+        let on_time = routing.schedule(&[0, 1, 2, 0], &distances);
+        let departures: Vec<f64> = on_time[0].iter().map(|visit| visit.departure).collect();
+        assert_eq!(departures, vec![0.0, 15.0, 35.0, 55.0]);
+        assert_eq!(on_time[0][2].start, 30.0);
+        assert_eq!(routing.time_window_violations(&[0, 1, 2, 0], &distances), 0);
+
+        let late = routing.schedule(&[0, 2, 1, 0], &distances);
+        assert_eq!(late[0][2].city, 1);
+        assert_eq!(late[0][2].lateness, 45.0 - 15.0);
+        assert_eq!(routing.time_window_violations(&[0, 2, 1, 0], &distances), 1);
+    }
+
+    #[test]
+    fn test_improve_routes_keeps_vehicles() {
+        // Two crossed routes on either side of the depot
+        let cities = vec![
+            City::new(0, 0.0, 0.0),
+            City::new(1, 10.0, 0.0),
+            City::new(2, 10.0, 10.0),
+            City::new(3, 20.0, 0.0),
+            City::new(4, 20.0, 10.0),
+            City::new(5, -10.0, 0.0),
+        ];
+        let distances = DistanceMatrix::from_cities(&cities);
+        let routing = VehicleRouting::new(0, vec![0.0, 1.0, 1.0, 1.0, 1.0, 1.0], 4.0).unwrap();
+        let mut route = vec![0, 1, 4, 3, 2, 0, 5, 0];
+        let before = distances.route_length(&route);
+
+        let gain = routing.improve_routes(&mut route, &distances, 5, |tour, local, neighbors| {
+            improve(tour, LocalSearchOperator::TwoOpt, local, neighbors)
+        });
+
+        assert!(gain > 0.0);
+        assert!((before - gain - distances.route_length(&route)).abs() < 1e-9);
+        assert_eq!(routing.split_routes(&route).len(), 2);
+        assert_eq!(&route[route.len() - 3..], &[0, 5, 0]);
+    }
+
+    // A colony over `cities` solving `routing`, run to the end
+    fn solve(cities: Vec<City>, routing: VehicleRouting, params: ACOParameters) -> Colony {
+        let mut colony = Colony::new(cities, params);
+        let problem = VehicleRoutingProblem::new(routing, colony.distances().clone(), 1.0).unwrap();
+        assert!(colony.set_problem(Box::new(problem)));
+        while colony.run_iteration() {}
+        colony
+    }
+
+    #[test]
+    fn test_vehicle_routing_colony() {
+        let cities = vec![
+            City::new(0, 0.0, 0.0),
+            City::new(1, 10.0, 0.0),
+            City::new(2, 0.0, 10.0),
+            City::new(3, -10.0, 0.0),
+            City::new(4, 0.0, -10.0),
+        ];
+        let short = VehicleRouting::new(0, vec![0.0, 1.0], 2.0).unwrap();
+        assert!(VehicleRoutingProblem::new(short, DistanceMatrix::from_cities(&cities), 1.0).is_none());
+
+        let params = ACOParameters {
+            num_ants: 5,
+            max_generations: 20,
+            seed: Some(11),
+            ..Default::default()
+        };
+        let routing = VehicleRouting::new(0, vec![0.0, 1.0, 1.0, 1.0, 1.0], 2.0).unwrap();
+        let colony = solve(cities, routing.clone(), params);
+        assert_eq!(colony.depot(), Some(0));
+
+        let routes = colony.best_vehicle_routes();
+        assert_eq!(routes.len(), 2);
+        let mut served: Vec<usize> = routes.iter().flat_map(|route| route[1..route.len() - 1].to_vec()).collect();
+        served.sort();
+        assert_eq!(served, vec![1, 2, 3, 4]);
+        assert!(routes.iter().all(|route| routing.route_load(route) <= 2.0));
+
+        let optimum = 2.0 * (20.0 + 200f64.sqrt());
+        assert!((colony.best_distance() - optimum).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_time_windows_colony() {
+        let cities = vec![
+            City::new(0, 0.0, 0.0),
+            City::new(1, 10.0, 0.0),
+            City::new(2, 10.0, 10.0),
+            City::new(3, 0.0, 10.0),
+        ];
+        let params = ACOParameters {
+            num_ants: 4,
+            max_generations: 10,
+            seed: Some(5),
+            local_search: LocalSearchMode::AllAnts,
+            ..Default::default()
+        };
+        // City 3 has to come first and city 1 last, so only one direction of the square works
+        let windows = TimeWindows::new(vec![0.0, 25.0, 0.0, 0.0], vec![100.0, 40.0, 40.0, 10.0], vec![0.0; 4]).unwrap();
+        let routing = VehicleRouting::new(0, vec![0.0; 4], f64::INFINITY).unwrap().with_time_windows(windows).unwrap();
+        let colony = solve(cities.clone(), routing, params.clone());
+
+        assert_eq!(colony.best_route(), Some(&vec![0, 3, 2, 1, 0]));
+        assert_eq!(colony.best_distance(), 40.0);
+        assert_eq!(colony.best_violations(), 0);
+        let summary = colony.best_summary();
+        assert_eq!(summary["time_window_violations"], 0);
+        let departures: Vec<f64> =
+            summary["schedules"][0].as_array().unwrap().iter().map(|visit| visit["departure"].as_f64().unwrap()).collect();
+        assert_eq!(departures, vec![0.0, 10.0, 20.0, 30.0, 40.0]);
+
+        // City 1 closes before any vehicle can get there: it is still served, late
+        let windows = TimeWindows::new(vec![0.0; 4], vec![100.0, 5.0, 100.0, 100.0], vec![0.0; 4]).unwrap();
+        let routing = VehicleRouting::new(0, vec![0.0; 4], f64::INFINITY).unwrap().with_time_windows(windows).unwrap();
+        let colony = solve(cities, routing.clone(), params);
+
+        assert_eq!(colony.best_violations(), 1);
+        let schedule = routing.schedule(colony.best_route().unwrap(), colony.distances());
+        let late = schedule.concat().into_iter().find(|visit| visit.lateness > 0.0).unwrap();
+        assert_eq!(late.city, 1);
+    }
+}
@@ -1,5 +1,6 @@
 use wasm_bindgen::prelude::*;
 use serde::{Deserialize, Serialize};
+use super::metric::DistanceMetric;
 
 #[wasm_bindgen]
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -7,13 +8,16 @@ pub struct City {
     id: u32,
     x: f64,
     y: f64,
+    // What visiting the city is worth in prize-collecting and orienteering tours
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    prize: Option<f64>,
 }
 
 #[wasm_bindgen]
 impl City {
     #[wasm_bindgen(constructor)]
     pub fn new(id: u32, x: f64, y: f64) -> City {
-        City { id, x, y }
+        City { id, x, y, prize: None }
     }
 
     #[wasm_bindgen(getter)]
@@ -31,11 +35,30 @@ impl City {
         self.y
     }
 
+    #[wasm_bindgen(getter)]
+    pub fn prize(&self) -> Option<f64> {
+        self.prize
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_prize(&mut self, prize: Option<f64>) {
+        self.prize = prize;
+    }
+
     #[wasm_bindgen]
     pub fn distance_to(&self, other: &City) -> f64 {
-        let dx = self.x - other.x;
-        let dy = self.y - other.y;
-        (dx * dx + dy * dy).sqrt()
+        self.distance_in(other, DistanceMetric::Euclidean)
+    }
+}
+
+impl City {
+    pub fn distance_in(&self, other: &City, metric: DistanceMetric) -> f64 {
+        metric.between(self, other)
+    }
+
+    pub fn with_prize(mut self, prize: f64) -> City {
+        self.prize = Some(prize);
+        self
     }
 }
 
@@ -49,6 +72,8 @@ mod tests {
         assert_eq!(city.id(), 0);
         assert_eq!(city.x(), 100.0);
         assert_eq!(city.y(), 200.0);
+        assert_eq!(city.prize(), None);
+        assert_eq!(city.with_prize(5.0).prize(), Some(5.0));
     }
 
     #[test]
@@ -57,6 +82,7 @@ mod tests {
         let city2 = City::new(1, 3.0, 4.0);
         let distance = city1.distance_to(&city2);
         assert_eq!(distance, 5.0);
+        assert_eq!(city1.distance_in(&city2, DistanceMetric::Manhattan), 7.0);
     }
 
     #[test]
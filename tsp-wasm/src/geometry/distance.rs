@@ -1,19 +1,148 @@
-use super::city::City;
+use super::{city::City, metric::DistanceMetric, route::RouteMode};
 
-pub fn calculate_distance(city1: &City, city2: &City) -> f64 {
-    city1.distance_to(city2)
+pub fn calculate_distance(city1: &City, city2: &City, metric: DistanceMetric) -> f64 {
+    city1.distance_in(city2, metric)
 }
 
-pub fn calculate_total_distance(cities: &[City]) -> f64 {
+// Length of visiting `cities` in order; only closed tours return to the first city
+pub fn calculate_total_distance(cities: &[City], mode: RouteMode, metric: DistanceMetric) -> f64 {
     if cities.len() < 2 {
         return 0.0;
     }
     
     let mut total = 0.0;
     for i in 0..cities.len() - 1 {
-        total += calculate_distance(&cities[i], &cities[i + 1]);
+        total += calculate_distance(&cities[i], &cities[i + 1], metric);
     }
     // Return to starting city
-    total += calculate_distance(&cities[cities.len() - 1], &cities[0]);
+    if mode.is_closed() {
+        total += calculate_distance(&cities[cities.len() - 1], &cities[0], metric);
+    }
     total
-}
\ No newline at end of file
+}
+
+// Dense n x n distance table, row-major
+#[derive(Debug, Clone, PartialEq)]
+pub struct DistanceMatrix {
+    size: usize,
+    data: Vec<f64>,
+}
+
+impl DistanceMatrix {
+    pub fn from_cities(cities: &[City]) -> Self {
+        Self::with_metric(cities, DistanceMetric::Euclidean)
+    }
+
+    pub fn with_metric(cities: &[City], metric: DistanceMetric) -> Self {
+        let size = cities.len();
+        let mut data = vec![0.0; size * size];
+        for i in 0..size {
+            for j in (i + 1)..size {
+                let distance = metric.between(&cities[i], &cities[j]);
+                data[i * size + j] = distance;
+                data[j * size + i] = distance;
+            }
+        }
+        DistanceMatrix { size, data }
+    }
+
+    pub fn from_fn(size: usize, mut distance: impl FnMut(usize, usize) -> f64) -> Self {
+        let mut data = vec![0.0; size * size];
+        for i in 0..size {
+            for j in 0..size {
+                if i != j {
+                    data[i * size + j] = distance(i, j);
+                }
+            }
+        }
+        DistanceMatrix { size, data }
+    }
+
+    // Row-major n x n costs; None unless the matrix is square with finite, non-negative entries.
+    // The diagonal is ignored
+    pub fn from_flat(data: Vec<f64>) -> Option<Self> {
+        let size = (data.len() as f64).sqrt().round() as usize;
+        if size * size != data.len() || data.iter().any(|&value| !value.is_finite() || value < 0.0) {
+            return None;
+        }
+        Some(Self::from_fn(size, |i, j| data[i * size + j]))
+    }
+
+    pub fn get(&self, i: usize, j: usize) -> f64 {
+        self.data[i * self.size + j]
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn is_symmetric(&self) -> bool {
+        (0..self.size).all(|i| ((i + 1)..self.size).all(|j| self.get(i, j) == self.get(j, i)))
+    }
+
+    pub fn route_length(&self, route: &[usize]) -> f64 {
+        route.windows(2).map(|edge| self.get(edge[0], edge[1])).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calculate_total_distance() {
+        let cities = vec![
+            City::new(0, 0.0, 0.0),
+            City::new(1, 3.0, 0.0),
+            City::new(2, 3.0, 4.0),
+        ];
+        assert_eq!(calculate_total_distance(&cities, RouteMode::Closed, DistanceMetric::Euclidean), 12.0);
+        assert_eq!(calculate_total_distance(&cities, RouteMode::Closed, DistanceMetric::Chebyshev), 11.0);
+        // Open paths are not charged the way back from the last city to the first
+        assert_eq!(calculate_total_distance(&cities, RouteMode::Open, DistanceMetric::Euclidean), 7.0);
+        assert_eq!(calculate_total_distance(&cities, RouteMode::FixedStartEnd { start: 0, end: 2 }, DistanceMetric::Euclidean), 7.0);
+    }
+
+    #[test]
+    fn test_distance_matrix() {
+        let cities = vec![
+            City::new(0, 0.0, 0.0),
+            City::new(1, 3.0, 0.0),
+            City::new(2, 3.0, 4.0),
+        ];
+        let matrix = DistanceMatrix::from_cities(&cities);
+
+        assert_eq!(matrix.size(), 3);
+        assert_eq!(matrix.get(0, 2), 5.0);
+        assert_eq!(matrix.get(2, 0), 5.0);
+        assert_eq!(matrix.get(1, 1), 0.0);
+        assert_eq!(matrix.route_length(&[0, 1, 2, 0]), 12.0);
+    }
+
+    #[test]
+    fn test_distance_matrix_from_flat() {
+        let matrix = DistanceMatrix::from_flat(vec![9.0, 1.0, 2.0, 0.0]).unwrap();
+        assert_eq!(matrix.size(), 2);
+        assert_eq!(matrix.get(0, 0), 0.0);
+        assert_eq!(matrix.get(1, 0), 2.0);
+        assert!(DistanceMatrix::from_flat(vec![0.0, 1.0, 2.0]).is_none());
+        assert!(DistanceMatrix::from_flat(vec![0.0, -1.0, 1.0, 0.0]).is_none());
+    }
+
+    #[test]
+    fn test_distance_matrix_with_metric() {
+        let cities = vec![City::new(0, 0.0, 0.0), City::new(1, 3.0, 4.0)];
+        assert_eq!(DistanceMatrix::with_metric(&cities, DistanceMetric::Manhattan).get(1, 0), 7.0);
+        assert_eq!(DistanceMatrix::with_metric(&cities, DistanceMetric::Chebyshev).get(0, 1), 4.0);
+    }
+
+    #[test]
+    fn test_distance_matrix_from_fn() {
+        let matrix = DistanceMatrix::from_fn(3, |i, j| (i * 10 + j) as f64);
+        assert_eq!(matrix.get(0, 2), 2.0);
+        assert_eq!(matrix.get(2, 1), 21.0);
+        assert_eq!(matrix.get(1, 1), 0.0);
+        assert!(!matrix.is_symmetric());
+        assert!(DistanceMatrix::from_fn(3, |i, j| (i + j) as f64).is_symmetric());
+    }
+}
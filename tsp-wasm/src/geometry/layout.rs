@@ -0,0 +1,46 @@
+use super::city::City;
+
+// Evenly spaced points on the unit circle, for instances without coordinates
+pub fn circle_layout(size: usize) -> Vec<(f64, f64)> {
+    (0..size)
+        .map(|i| {
+            let angle = i as f64 * std::f64::consts::TAU / size as f64;
+            (angle.cos(), angle.sin())
+        })
+        .collect()
+}
+
+// Scales points uniformly into the canvas, keeping their aspect ratio and a margin for the city markers
+pub fn fit_to_canvas(points: &[(f64, f64)], width: f64, height: f64) -> Vec<City> {
+    const MARGIN: f64 = 20.0;
+    let (min_x, max_x) = points.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), p| (lo.min(p.0), hi.max(p.0)));
+    let (min_y, max_y) = points.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), p| (lo.min(p.1), hi.max(p.1)));
+    let span = (max_x - min_x).max(max_y - min_y).max(f64::EPSILON);
+    let scale = ((width - 2.0 * MARGIN) / span).min((height - 2.0 * MARGIN) / span);
+
+    points
+        .iter()
+        .enumerate()
+        .map(|(i, &(x, y))| City::new(i as u32, MARGIN + (x - min_x) * scale, MARGIN + (y - min_y) * scale))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_circle_layout() {
+        let points = circle_layout(4);
+        assert_eq!(points.len(), 4);
+        assert!((points[1].0).abs() < 1e-12 && (points[1].1 - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_fit_to_canvas() {
+        let cities = fit_to_canvas(&[(10.0, 10.0), (20.0, 15.0)], 240.0, 240.0);
+        assert_eq!((cities[0].x(), cities[0].y()), (20.0, 20.0));
+        assert_eq!((cities[1].x(), cities[1].y()), (220.0, 120.0));
+        assert_eq!(cities[1].id(), 1);
+    }
+}
@@ -0,0 +1,154 @@
+use super::city::City;
+
+// How the distance between two points is measured. The geographic metrics read x as
+// longitude and y as latitude; Geo expects TSPLIB DDD.MM values, Haversine decimal degrees.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DistanceMetric {
+    #[default]
+    Euclidean,
+    // TSPLIB EUC_2D: Euclidean rounded to the nearest integer
+    RoundedEuclidean,
+    // TSPLIB CEIL_2D
+    Ceiling,
+    Manhattan,
+    Chebyshev,
+    // TSPLIB ATT pseudo-Euclidean distance
+    Att,
+    // TSPLIB GEO great-circle distance in whole kilometres
+    Geo,
+    // Great-circle distance in kilometres
+    Haversine,
+}
+
+impl DistanceMetric {
+    pub fn from_name(name: &str) -> Option<DistanceMetric> {
+        match name.to_ascii_lowercase().as_str() {
+            "euclidean" => Some(DistanceMetric::Euclidean),
+            "rounded" | "nint" | "euc_2d" => Some(DistanceMetric::RoundedEuclidean),
+            "ceil" | "ceiling" | "ceil_2d" => Some(DistanceMetric::Ceiling),
+            "manhattan" | "man_2d" => Some(DistanceMetric::Manhattan),
+            "chebyshev" | "max_2d" => Some(DistanceMetric::Chebyshev),
+            "att" => Some(DistanceMetric::Att),
+            "geo" => Some(DistanceMetric::Geo),
+            "haversine" => Some(DistanceMetric::Haversine),
+            _ => None,
+        }
+    }
+
+    pub fn distance(&self, a: (f64, f64), b: (f64, f64)) -> f64 {
+        let dx = a.0 - b.0;
+        let dy = a.1 - b.1;
+        match self {
+            DistanceMetric::Euclidean => (dx * dx + dy * dy).sqrt(),
+            DistanceMetric::RoundedEuclidean => nint((dx * dx + dy * dy).sqrt()),
+            DistanceMetric::Ceiling => (dx * dx + dy * dy).sqrt().ceil(),
+            DistanceMetric::Manhattan => dx.abs() + dy.abs(),
+            DistanceMetric::Chebyshev => dx.abs().max(dy.abs()),
+            DistanceMetric::Att => {
+                let r = ((dx * dx + dy * dy) / 10.0).sqrt();
+                let t = nint(r);
+                if t < r {
+                    t + 1.0
+                } else {
+                    t
+                }
+            }
+            DistanceMetric::Geo => {
+                const EARTH_RADIUS: f64 = 6378.388;
+                let (lon_a, lat_a) = (geo_radians(a.0), geo_radians(a.1));
+                let (lon_b, lat_b) = (geo_radians(b.0), geo_radians(b.1));
+                let q1 = (lon_a - lon_b).cos();
+                let q2 = (lat_a - lat_b).cos();
+                let q3 = (lat_a + lat_b).cos();
+                (EARTH_RADIUS * (0.5 * ((1.0 + q1) * q2 - (1.0 - q1) * q3)).acos() + 1.0).trunc()
+            }
+            DistanceMetric::Haversine => {
+                const EARTH_RADIUS: f64 = 6371.0;
+                let (lat_a, lat_b) = (a.1.to_radians(), b.1.to_radians());
+                let h = (dy.to_radians() / 2.0).sin().powi(2)
+                    + lat_a.cos() * lat_b.cos() * (dx.to_radians() / 2.0).sin().powi(2);
+                2.0 * EARTH_RADIUS * h.sqrt().min(1.0).asin()
+            }
+        }
+    }
+
+    // Geo and Haversine, which only make sense for longitude/latitude points
+    pub fn is_geographic(&self) -> bool {
+        matches!(self, DistanceMetric::Geo | DistanceMetric::Haversine)
+    }
+
+    // Whether `(x, y)` can be a longitude/latitude point when the metric is geographic
+    pub fn accepts(&self, point: (f64, f64)) -> bool {
+        !self.is_geographic() || (point.0.abs() <= 180.0 && point.1.abs() <= 90.0)
+    }
+
+    pub fn between(&self, a: &City, b: &City) -> f64 {
+        self.distance((a.x(), a.y()), (b.x(), b.y()))
+    }
+}
+
+fn nint(value: f64) -> f64 {
+    (value + 0.5).floor()
+}
+
+// GEO coordinates are DDD.MM (degrees and minutes); TSPLIB fixes pi to 3.141592
+fn geo_radians(value: f64) -> f64 {
+    #[allow(clippy::approx_constant)]
+    const PI: f64 = 3.141592;
+    let degrees = value.trunc();
+    let minutes = value - degrees;
+    PI * (degrees + 5.0 * minutes / 3.0) / 180.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_planar_metrics() {
+        let (a, b) = ((0.0, 0.0), (3.2, -4.1));
+        assert!((DistanceMetric::Euclidean.distance(a, b) - 5.2009614).abs() < 1e-6);
+        assert_eq!(DistanceMetric::RoundedEuclidean.distance(a, b), 5.0);
+        assert_eq!(DistanceMetric::Ceiling.distance(a, b), 6.0);
+        assert!((DistanceMetric::Manhattan.distance(a, b) - 7.3).abs() < 1e-12);
+        assert_eq!(DistanceMetric::Chebyshev.distance(a, b), 4.1);
+    }
+
+    #[test]
+    fn test_att_distance() {
+        // sqrt(1000 / 10) = 10 exactly, sqrt(1010 / 10) rounds down to 10 and is bumped to 11
+        assert_eq!(DistanceMetric::Att.distance((0.0, 0.0), (0.0, 1000f64.sqrt())), 10.0);
+        assert_eq!(DistanceMetric::Att.distance((0.0, 0.0), (0.0, 1010f64.sqrt())), 11.0);
+    }
+
+    #[test]
+    fn test_geographic_metrics() {
+        // One degree of longitude on the equator
+        assert_eq!(DistanceMetric::Geo.distance((0.0, 0.0), (1.0, 0.0)), 112.0);
+        // 0.30 is 30 minutes, i.e. half a degree, in either direction
+        assert_eq!(DistanceMetric::Geo.distance((0.0, 0.0), (0.30, 0.0)), 56.0);
+        assert_eq!(DistanceMetric::Geo.distance((0.0, 0.0), (0.0, 0.30)), 56.0);
+
+        // Paris to London is about 344 km
+        let paris = City::new(0, 2.3522, 48.8566);
+        let london = City::new(1, -0.1276, 51.5072);
+        assert!((DistanceMetric::Haversine.between(&paris, &london) - 344.0).abs() < 2.0);
+    }
+
+    #[test]
+    fn test_metric_from_name() {
+        assert_eq!(DistanceMetric::from_name("EUC_2D"), Some(DistanceMetric::RoundedEuclidean));
+        assert_eq!(DistanceMetric::from_name("haversine"), Some(DistanceMetric::Haversine));
+        assert_eq!(DistanceMetric::from_name("EUC_3D"), None);
+    }
+
+    #[test]
+    fn test_geographic_points() {
+        assert!(DistanceMetric::Haversine.is_geographic());
+        assert!(!DistanceMetric::Att.is_geographic());
+        assert!(DistanceMetric::Geo.accepts((-179.5, 89.0)));
+        // Canvas pixels are far outside the longitude/latitude range
+        assert!(!DistanceMetric::Haversine.accepts((400.0, 300.0)));
+        assert!(DistanceMetric::Euclidean.accepts((400.0, 300.0)));
+    }
+}
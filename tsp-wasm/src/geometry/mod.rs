@@ -1,2 +1,6 @@
 pub mod city;
-pub mod distance;
\ No newline at end of file
+pub mod distance;
+pub mod layout;
+pub mod metric;
+pub mod neighbors;
+pub mod route;
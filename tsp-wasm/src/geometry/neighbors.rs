@@ -0,0 +1,63 @@
+use super::distance::DistanceMatrix;
+
+// For every city, the indices of its k closest cities ordered by distance
+#[derive(Debug, Clone)]
+pub struct NeighborLists {
+    k: usize,
+    lists: Vec<usize>,
+}
+
+impl NeighborLists {
+    pub fn new(distances: &DistanceMatrix, k: usize) -> Self {
+        let n = distances.size();
+        let k = k.min(n.saturating_sub(1));
+        let mut lists = Vec::with_capacity(n * k);
+
+        let mut others: Vec<usize> = Vec::with_capacity(n);
+        for i in 0..n {
+            others.clear();
+            others.extend((0..n).filter(|&j| j != i));
+            others.sort_by(|&a, &b| distances.get(i, a).total_cmp(&distances.get(i, b)));
+            lists.extend_from_slice(&others[..k]);
+        }
+
+        NeighborLists { k, lists }
+    }
+
+    pub fn neighbors(&self, city: usize) -> &[usize] {
+        &self.lists[city * self.k..(city + 1) * self.k]
+    }
+
+    pub fn k(&self) -> usize {
+        self.k
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::city::City;
+
+    #[test]
+    fn test_neighbor_lists() {
+        let cities = vec![
+            City::new(0, 0.0, 0.0),
+            City::new(1, 5.0, 0.0),
+            City::new(2, 1.0, 0.0),
+            City::new(3, 3.0, 0.0),
+        ];
+        let distances = DistanceMatrix::from_cities(&cities);
+        let lists = NeighborLists::new(&distances, 2);
+
+        assert_eq!(lists.k(), 2);
+        assert_eq!(lists.neighbors(0), &[2, 3]);
+        assert_eq!(lists.neighbors(1), &[3, 2]);
+    }
+
+    #[test]
+    fn test_neighbor_lists_truncated_to_city_count() {
+        let cities = vec![City::new(0, 0.0, 0.0), City::new(1, 1.0, 0.0)];
+        let lists = NeighborLists::new(&DistanceMatrix::from_cities(&cities), 10);
+        assert_eq!(lists.neighbors(0), &[1]);
+    }
+}
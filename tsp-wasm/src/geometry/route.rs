@@ -0,0 +1,71 @@
+// Shape of the routes being built. Every mode except Closed produces an open path that does not
+// return to its first city.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RouteMode {
+    #[default]
+    Closed,
+    Open,
+    FixedStart { start: usize },
+    FixedStartEnd { start: usize, end: usize },
+}
+
+impl RouteMode {
+    // "closed", "open", "fixed-start" (needs `start`) or "fixed-start-end" (needs distinct `start` and `end`)
+    pub fn from_parts(name: &str, start: Option<usize>, end: Option<usize>) -> Option<RouteMode> {
+        match (name.to_ascii_lowercase().as_str(), start, end) {
+            ("closed" | "tour", _, _) => Some(RouteMode::Closed),
+            ("open" | "path", _, _) => Some(RouteMode::Open),
+            ("fixed-start", Some(start), _) => Some(RouteMode::FixedStart { start }),
+            ("fixed-start-end", Some(start), Some(end)) if start != end => {
+                Some(RouteMode::FixedStartEnd { start, end })
+            }
+            _ => None,
+        }
+    }
+
+    pub fn is_closed(&self) -> bool {
+        *self == RouteMode::Closed
+    }
+
+    pub fn start(&self) -> Option<usize> {
+        match *self {
+            RouteMode::FixedStart { start } | RouteMode::FixedStartEnd { start, .. } => Some(start),
+            _ => None,
+        }
+    }
+
+    pub fn end(&self) -> Option<usize> {
+        match *self {
+            RouteMode::FixedStartEnd { end, .. } => Some(end),
+            _ => None,
+        }
+    }
+
+    // Whether the fixed cities exist among `num_cities` cities
+    pub fn fits(&self, num_cities: usize) -> bool {
+        self.start().is_none_or(|start| start < num_cities) && self.end().is_none_or(|end| end < num_cities)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_route_mode_from_parts() {
+        assert_eq!(RouteMode::from_parts("open", None, None), Some(RouteMode::Open));
+        assert_eq!(RouteMode::from_parts("fixed-start", Some(2), None), Some(RouteMode::FixedStart { start: 2 }));
+        assert_eq!(RouteMode::from_parts("fixed-start", None, None), None);
+        assert_eq!(RouteMode::from_parts("fixed-start-end", Some(1), Some(1)), None);
+    }
+
+    #[test]
+    fn test_route_mode_endpoints() {
+        let mode = RouteMode::FixedStartEnd { start: 0, end: 4 };
+        assert!(!mode.is_closed());
+        assert_eq!((mode.start(), mode.end()), (Some(0), Some(4)));
+        assert!(mode.fits(5));
+        assert!(!mode.fits(4));
+        assert!(RouteMode::Closed.fits(0));
+    }
+}
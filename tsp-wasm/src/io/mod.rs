@@ -0,0 +1,2 @@
+pub mod tsplib;
+pub mod qaplib;
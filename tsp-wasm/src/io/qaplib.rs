@@ -0,0 +1,133 @@
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum QaplibError {
+    MissingSize,
+    InvalidNumber(String),
+    WrongEntryCount { expected: usize, found: usize },
+    InvalidAssignment,
+}
+
+impl fmt::Display for QaplibError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QaplibError::MissingSize => write!(f, "missing or invalid instance size"),
+            QaplibError::InvalidNumber(token) => write!(f, "invalid number '{}'", token),
+            QaplibError::WrongEntryCount { expected, found } => {
+                write!(f, "expected {} entries, found {}", expected, found)
+            }
+            QaplibError::InvalidAssignment => write!(f, "assignment is not a permutation of the locations"),
+        }
+    }
+}
+
+// A QAPLIB .dat file: the size n followed by two n x n matrices, the flows between facilities and
+// the distances between locations, in free whitespace-separated format
+#[derive(Debug, Clone, PartialEq)]
+pub struct QaplibInstance {
+    pub size: usize,
+    // Row-major
+    pub flow: Vec<f64>,
+    pub distance: Vec<f64>,
+}
+
+// A QAPLIB .sln file: size, objective value and the one-based location of every facility
+#[derive(Debug, Clone, PartialEq)]
+pub struct QaplibSolution {
+    pub size: usize,
+    pub cost: f64,
+    // Zero-based location of each facility
+    pub assignment: Vec<usize>,
+}
+
+fn parse_numbers(text: &str) -> Result<Vec<f64>, QaplibError> {
+    text.split_whitespace()
+        .map(|token| token.parse::<f64>().map_err(|_| QaplibError::InvalidNumber(token.to_string())))
+        .collect()
+}
+
+fn parse_size(value: Option<&f64>) -> Result<usize, QaplibError> {
+    match value {
+        Some(&size) if size >= 1.0 && size.fract() == 0.0 => Ok(size as usize),
+        _ => Err(QaplibError::MissingSize),
+    }
+}
+
+pub fn parse_qap(text: &str) -> Result<QaplibInstance, QaplibError> {
+    let numbers = parse_numbers(text)?;
+    let size = parse_size(numbers.first())?;
+    let entries = &numbers[1..];
+    // A size too large to count its entries can never match them
+    let expected = size.checked_mul(size).and_then(|square| square.checked_mul(2)).ok_or(QaplibError::MissingSize)?;
+    if entries.len() != expected {
+        return Err(QaplibError::WrongEntryCount { expected, found: entries.len() });
+    }
+
+    let (flow, distance) = entries.split_at(size * size);
+    Ok(QaplibInstance {
+        size,
+        flow: flow.to_vec(),
+        distance: distance.to_vec(),
+    })
+}
+
+pub fn parse_solution(text: &str) -> Result<QaplibSolution, QaplibError> {
+    let numbers = parse_numbers(text)?;
+    let size = parse_size(numbers.first())?;
+    let cost = *numbers.get(1).ok_or(QaplibError::WrongEntryCount { expected: size + 2, found: numbers.len() })?;
+    let entries = &numbers[2..];
+    if entries.len() != size {
+        return Err(QaplibError::WrongEntryCount { expected: size, found: entries.len() });
+    }
+
+    let mut seen = vec![false; size];
+    let mut assignment = Vec::with_capacity(size);
+    for &location in entries {
+        if location < 1.0 || location > size as f64 || location.fract() != 0.0 || seen[location as usize - 1] {
+            return Err(QaplibError::InvalidAssignment);
+        }
+        seen[location as usize - 1] = true;
+        assignment.push(location as usize - 1);
+    }
+    Ok(QaplibSolution { size, cost, assignment })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INSTANCE: &str = "3
+
+ 0 5 2
+ 5 0 3
+ 2 3 0
+
+ 0 8 15
+ 8 0 13
+ 15 13 0
+";
+
+    #[test]
+    fn test_parse_qap() {
+        let instance = parse_qap(INSTANCE).unwrap();
+        assert_eq!(instance.size, 3);
+        assert_eq!(instance.flow[1], 5.0);
+        assert_eq!(instance.distance[2 * 3 + 1], 13.0);
+
+        assert_eq!(parse_qap("2\n0 1\n1 0\n"), Err(QaplibError::WrongEntryCount { expected: 8, found: 4 }));
+        assert_eq!(parse_qap("x"), Err(QaplibError::InvalidNumber("x".to_string())));
+        assert_eq!(parse_qap("1e19
+0 1
+"), Err(QaplibError::MissingSize));
+    }
+
+    #[test]
+    fn test_parse_solution() {
+        let solution = parse_solution("3 134\n 2 1 3\n").unwrap();
+        assert_eq!(solution.cost, 134.0);
+        assert_eq!(solution.assignment, vec![1, 0, 2]);
+
+        assert_eq!(parse_solution("3 134\n 2 2 3\n"), Err(QaplibError::InvalidAssignment));
+        assert_eq!(parse_solution("3 134\n 2 1\n"), Err(QaplibError::WrongEntryCount { expected: 3, found: 2 }));
+    }
+}
@@ -0,0 +1,560 @@
+use crate::geometry::{distance::DistanceMatrix, metric::DistanceMetric};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdgeWeightType {
+    Euc2d,
+    Ceil2d,
+    Man2d,
+    Max2d,
+    Att,
+    Geo,
+    Explicit,
+}
+
+impl EdgeWeightType {
+    pub fn from_name(name: &str) -> Option<EdgeWeightType> {
+        match name {
+            "EUC_2D" => Some(EdgeWeightType::Euc2d),
+            "CEIL_2D" => Some(EdgeWeightType::Ceil2d),
+            "MAN_2D" => Some(EdgeWeightType::Man2d),
+            "MAX_2D" => Some(EdgeWeightType::Max2d),
+            "ATT" => Some(EdgeWeightType::Att),
+            "GEO" => Some(EdgeWeightType::Geo),
+            "EXPLICIT" => Some(EdgeWeightType::Explicit),
+            _ => None,
+        }
+    }
+
+    // TSPLIB rounds MAN_2D and MAX_2D to the nearest integer as well, which only matters for
+    // fractional coordinates
+    pub fn metric(&self) -> Option<DistanceMetric> {
+        match self {
+            EdgeWeightType::Euc2d => Some(DistanceMetric::RoundedEuclidean),
+            EdgeWeightType::Ceil2d => Some(DistanceMetric::Ceiling),
+            EdgeWeightType::Man2d => Some(DistanceMetric::Manhattan),
+            EdgeWeightType::Max2d => Some(DistanceMetric::Chebyshev),
+            EdgeWeightType::Att => Some(DistanceMetric::Att),
+            EdgeWeightType::Geo => Some(DistanceMetric::Geo),
+            EdgeWeightType::Explicit => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TsplibError {
+    MissingDimension,
+    UnsupportedEdgeWeightType(String),
+    UnsupportedEdgeWeightFormat(String),
+    InvalidNumber(String),
+    InvalidNode(usize),
+    MissingSection(&'static str),
+    WrongEntryCount { expected: usize, found: usize },
+    InvalidTour,
+}
+
+impl fmt::Display for TsplibError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TsplibError::MissingDimension => write!(f, "missing or invalid DIMENSION"),
+            TsplibError::UnsupportedEdgeWeightType(name) => write!(f, "unsupported EDGE_WEIGHT_TYPE {}", name),
+            TsplibError::UnsupportedEdgeWeightFormat(name) => write!(f, "unsupported EDGE_WEIGHT_FORMAT {}", name),
+            TsplibError::InvalidNumber(token) => write!(f, "invalid number '{}'", token),
+            TsplibError::InvalidNode(id) => write!(f, "node {} is out of range", id),
+            TsplibError::MissingSection(name) => write!(f, "missing {}", name),
+            TsplibError::WrongEntryCount { expected, found } => {
+                write!(f, "expected {} entries, found {}", expected, found)
+            }
+            TsplibError::InvalidTour => write!(f, "tour does not visit every node exactly once"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TsplibInstance {
+    pub name: String,
+    pub comment: String,
+    pub dimension: usize,
+    pub edge_weight_type: EdgeWeightType,
+    // Node coordinates, or DISPLAY_DATA_SECTION for explicit instances; only used for drawing
+    // when the weights are explicit
+    pub coordinates: Option<Vec<(f64, f64)>>,
+    pub distances: DistanceMatrix,
+    // CVRP instances: vehicle capacity, demand per node and zero-based depot nodes
+    pub capacity: Option<f64>,
+    pub demands: Option<Vec<f64>>,
+    pub depots: Vec<usize>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Section {
+    Header,
+    NodeCoords,
+    DisplayData,
+    EdgeWeights,
+    Demands,
+    Depots,
+    Ignored,
+}
+
+pub fn parse_tsp(text: &str) -> Result<TsplibInstance, TsplibError> {
+    let mut name = String::new();
+    let mut comment = String::new();
+    let mut dimension = None;
+    let mut edge_weight_type = None;
+    let mut edge_weight_format = String::from("FULL_MATRIX");
+    let mut capacity = None;
+    let mut demands: Vec<Option<f64>> = Vec::new();
+    let mut depots = Vec::new();
+    let mut node_coords: Vec<Option<(f64, f64)>> = Vec::new();
+    let mut display_data: Vec<Option<(f64, f64)>> = Vec::new();
+    let mut weights = Vec::new();
+    let mut section = Section::Header;
+
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        // Keywords start with a letter, section data with a number or sign
+        if line.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            let (key, value) = match line.split_once(':') {
+                Some((key, value)) => (key.trim(), value.trim()),
+                None => (line, ""),
+            };
+            section = match key {
+                "NAME" => {
+                    name = value.to_string();
+                    Section::Header
+                }
+                "COMMENT" => {
+                    comment = value.to_string();
+                    Section::Header
+                }
+                "DIMENSION" => {
+                    dimension = Some(value.parse::<usize>().map_err(|_| TsplibError::MissingDimension)?);
+                    Section::Header
+                }
+                "EDGE_WEIGHT_TYPE" => {
+                    edge_weight_type = Some(
+                        EdgeWeightType::from_name(value)
+                            .ok_or_else(|| TsplibError::UnsupportedEdgeWeightType(value.to_string()))?,
+                    );
+                    Section::Header
+                }
+                "EDGE_WEIGHT_FORMAT" => {
+                    edge_weight_format = value.to_string();
+                    Section::Header
+                }
+                "CAPACITY" => {
+                    capacity = Some(value.parse::<f64>().map_err(|_| TsplibError::InvalidNumber(value.to_string()))?);
+                    Section::Header
+                }
+                "NODE_COORD_SECTION" => Section::NodeCoords,
+                "DEMAND_SECTION" => {
+                    demands.resize(dimension.ok_or(TsplibError::MissingDimension)?, None);
+                    Section::Demands
+                }
+                "DEPOT_SECTION" => Section::Depots,
+                "DISPLAY_DATA_SECTION" => Section::DisplayData,
+                "EDGE_WEIGHT_SECTION" => Section::EdgeWeights,
+                "EOF" => break,
+                _ => Section::Ignored,
+            };
+            if section == Section::NodeCoords || section == Section::DisplayData {
+                let size = dimension.ok_or(TsplibError::MissingDimension)?;
+                node_coords.resize(size, None);
+                display_data.resize(size, None);
+            }
+            continue;
+        }
+
+        match section {
+            Section::NodeCoords | Section::DisplayData => {
+                let values = parse_numbers(line)?;
+                if values.len() < 3 {
+                    return Err(TsplibError::WrongEntryCount { expected: 3, found: values.len() });
+                }
+                let id = node_id(values[0])?;
+                let target = if section == Section::NodeCoords { &mut node_coords } else { &mut display_data };
+                if id == 0 || id > target.len() {
+                    return Err(TsplibError::InvalidNode(id));
+                }
+                target[id - 1] = Some((values[1], values[2]));
+            }
+            Section::EdgeWeights => weights.extend(parse_numbers(line)?),
+            Section::Demands => {
+                let values = parse_numbers(line)?;
+                if values.len() < 2 {
+                    return Err(TsplibError::WrongEntryCount { expected: 2, found: values.len() });
+                }
+                let id = node_id(values[0])?;
+                if id > demands.len() {
+                    return Err(TsplibError::InvalidNode(id));
+                }
+                demands[id - 1] = Some(values[1]);
+            }
+            // Depot ids, terminated by -1
+            Section::Depots => {
+                for value in parse_numbers(line)? {
+                    if value < 0.0 {
+                        section = Section::Ignored;
+                        break;
+                    }
+                    let id = node_id(value)?;
+                    if id > dimension.unwrap_or(0) {
+                        return Err(TsplibError::InvalidNode(id));
+                    }
+                    depots.push(id - 1);
+                }
+            }
+            Section::Header | Section::Ignored => {}
+        }
+    }
+
+    let dimension = dimension.ok_or(TsplibError::MissingDimension)?;
+    let edge_weight_type = edge_weight_type.unwrap_or(EdgeWeightType::Euc2d);
+
+    let coordinates = |points: Vec<Option<(f64, f64)>>| -> Option<Vec<(f64, f64)>> {
+        points.into_iter().collect::<Option<Vec<_>>>().filter(|points| !points.is_empty())
+    };
+
+    let (distances, coordinates) = match edge_weight_type.metric() {
+        None => {
+            if weights.is_empty() {
+                return Err(TsplibError::MissingSection("EDGE_WEIGHT_SECTION"));
+            }
+            let distances = explicit_matrix(dimension, &edge_weight_format, &weights)?;
+            let display = coordinates(display_data).or_else(|| coordinates(node_coords));
+            (distances, display)
+        }
+        Some(metric) => {
+            let points = coordinates(node_coords).ok_or(TsplibError::MissingSection("NODE_COORD_SECTION"))?;
+            // GEO nodes are listed as latitude, longitude
+            let point = |i: usize| match edge_weight_type {
+                EdgeWeightType::Geo => (points[i].1, points[i].0),
+                _ => points[i],
+            };
+            let distances = DistanceMatrix::from_fn(dimension, |i, j| metric.distance(point(i), point(j)));
+            (distances, Some(points))
+        }
+    };
+
+    let demands = if demands.is_empty() {
+        None
+    } else {
+        Some(demands.into_iter().collect::<Option<Vec<_>>>().ok_or(TsplibError::MissingSection("DEMAND_SECTION"))?)
+    };
+
+    Ok(TsplibInstance {
+        name,
+        comment,
+        dimension,
+        edge_weight_type,
+        coordinates,
+        distances,
+        capacity,
+        demands,
+        depots,
+    })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TsplibTour {
+    pub name: String,
+    // Zero-based city indices, without returning to the first city
+    pub tour: Vec<usize>,
+}
+
+impl TsplibTour {
+    // Closed route in the form used by `Colony::best_route`
+    pub fn route(&self) -> Vec<usize> {
+        let mut route = self.tour.clone();
+        route.extend(self.tour.first().copied());
+        route
+    }
+}
+
+// Reads the first tour of a TSPLIB .tour file; TOUR_SECTION lists one-based nodes ending with -1
+pub fn parse_tour(text: &str) -> Result<TsplibTour, TsplibError> {
+    let mut name = String::new();
+    let mut dimension = None;
+    let mut tour: Option<Vec<usize>> = None;
+    let mut in_section = false;
+
+    'lines: for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if line.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            let (key, value) = match line.split_once(':') {
+                Some((key, value)) => (key.trim(), value.trim()),
+                None => (line, ""),
+            };
+            match key {
+                "NAME" => name = value.to_string(),
+                "DIMENSION" => dimension = Some(value.parse::<usize>().map_err(|_| TsplibError::MissingDimension)?),
+                "TOUR_SECTION" => {
+                    in_section = true;
+                    tour = Some(Vec::new());
+                }
+                "EOF" => break,
+                _ => {}
+            }
+            continue;
+        }
+
+        if let (true, Some(nodes)) = (in_section, tour.as_mut()) {
+            for value in parse_numbers(line)? {
+                if value < 0.0 {
+                    break 'lines;
+                }
+                nodes.push(node_id(value)?);
+            }
+        }
+    }
+
+    let tour = tour.ok_or(TsplibError::MissingSection("TOUR_SECTION"))?;
+    let dimension = dimension.unwrap_or(tour.len());
+    if tour.len() != dimension {
+        return Err(TsplibError::WrongEntryCount { expected: dimension, found: tour.len() });
+    }
+
+    let mut seen = vec![false; dimension];
+    for &node in &tour {
+        if node == 0 || node > dimension || seen[node - 1] {
+            return Err(TsplibError::InvalidTour);
+        }
+        seen[node - 1] = true;
+    }
+
+    Ok(TsplibTour {
+        name,
+        tour: tour.into_iter().map(|node| node - 1).collect(),
+    })
+}
+
+fn without_return(route: &[usize]) -> &[usize] {
+    match route {
+        [first, .., last] if first == last => &route[..route.len() - 1],
+        _ => route,
+    }
+}
+
+// Whether a route (closed or not) visits each of `dimension` nodes exactly once, as a TSPLIB tour does
+pub fn is_tour(route: &[usize], dimension: usize) -> bool {
+    let tour = without_return(route);
+    if tour.len() != dimension {
+        return false;
+    }
+    let mut seen = vec![false; dimension];
+    for &node in tour {
+        if node >= dimension || seen[node] {
+            return false;
+        }
+        seen[node] = true;
+    }
+    true
+}
+
+// Serialises a route (closed or not) as a TSPLIB .tour file
+pub fn write_tour(name: &str, comment: &str, route: &[usize]) -> String {
+    let tour = without_return(route);
+
+    let mut text = format!("NAME : {}\nCOMMENT : {}\nTYPE : TOUR\nDIMENSION : {}\nTOUR_SECTION\n", name, comment, tour.len());
+    for &city in tour {
+        text.push_str(&format!("{}\n", city + 1));
+    }
+    text.push_str("-1\nEOF\n");
+    text
+}
+
+// Percentage by which `distance` exceeds the known optimum
+pub fn optimality_gap(distance: f64, optimum: f64) -> f64 {
+    if optimum <= 0.0 || !distance.is_finite() {
+        return f64::INFINITY;
+    }
+    (distance - optimum) / optimum * 100.0
+}
+
+fn parse_numbers(line: &str) -> Result<Vec<f64>, TsplibError> {
+    line.split_whitespace()
+        .map(|token| token.parse::<f64>().map_err(|_| TsplibError::InvalidNumber(token.to_string())))
+        .collect()
+}
+
+// Node ids are 1-based integers
+fn node_id(value: f64) -> Result<usize, TsplibError> {
+    if value < 1.0 || value.fract() != 0.0 {
+        return Err(TsplibError::InvalidNode(value as usize));
+    }
+    Ok(value as usize)
+}
+
+fn explicit_matrix(size: usize, format: &str, weights: &[f64]) -> Result<DistanceMatrix, TsplibError> {
+    let mut data = vec![0.0; size * size];
+    let mut entries = weights.iter();
+    let mut expected = 0;
+
+    {
+        let mut fill = |i: usize, j: usize, symmetric: bool| {
+            expected += 1;
+            if let Some(&value) = entries.next() {
+                data[i * size + j] = value;
+                if symmetric {
+                    data[j * size + i] = value;
+                }
+            }
+        };
+
+        match format {
+            "FULL_MATRIX" => (0..size).for_each(|i| (0..size).for_each(|j| fill(i, j, false))),
+            "UPPER_ROW" => (0..size).for_each(|i| ((i + 1)..size).for_each(|j| fill(i, j, true))),
+            "UPPER_DIAG_ROW" => (0..size).for_each(|i| (i..size).for_each(|j| fill(i, j, true))),
+            "LOWER_ROW" => (0..size).for_each(|i| (0..i).for_each(|j| fill(i, j, true))),
+            "LOWER_DIAG_ROW" => (0..size).for_each(|i| (0..=i).for_each(|j| fill(i, j, true))),
+            _ => return Err(TsplibError::UnsupportedEdgeWeightFormat(format.to_string())),
+        }
+    }
+
+    if weights.len() != expected {
+        return Err(TsplibError::WrongEntryCount { expected, found: weights.len() });
+    }
+    Ok(DistanceMatrix::from_fn(size, |i, j| data[i * size + j]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_euc_2d() {
+        let text = "NAME : square4\nCOMMENT : unit test\nTYPE : TSP\nDIMENSION : 4\nEDGE_WEIGHT_TYPE : EUC_2D\n\
+                    NODE_COORD_SECTION\n1 0 0\n2 3.2 0\n3 3.2 4.1\n4 0 4.1\nEOF\n";
+        let instance = parse_tsp(text).unwrap();
+
+        assert_eq!(instance.name, "square4");
+        assert_eq!(instance.dimension, 4);
+        assert_eq!(instance.edge_weight_type, EdgeWeightType::Euc2d);
+        assert_eq!(instance.coordinates.as_ref().unwrap()[2], (3.2, 4.1));
+        assert_eq!(instance.distances.get(0, 1), 3.0);
+        assert_eq!(instance.distances.get(0, 2), 5.0);
+        assert_eq!(instance.distances.route_length(&[0, 1, 2, 3, 0]), 14.0);
+    }
+
+    #[test]
+    fn test_parse_geo() {
+        // Latitude first: the second node is one degree north, the third one degree east
+        let text = "DIMENSION: 3\nEDGE_WEIGHT_TYPE: GEO\nNODE_COORD_SECTION\n1 0.0 0.0\n2 1.0 0.0\n3 0.0 1.0\n";
+        let instance = parse_tsp(text).unwrap();
+        assert_eq!(instance.distances.get(0, 1), 112.0);
+        assert_eq!(instance.distances.get(0, 2), 112.0);
+        assert_eq!(instance.distances.get(1, 2), 158.0);
+    }
+
+    #[test]
+    fn test_explicit_formats_agree() {
+        let header = "DIMENSION: 3\nEDGE_WEIGHT_TYPE: EXPLICIT\n";
+        let full = format!("{}EDGE_WEIGHT_FORMAT: FULL_MATRIX\nEDGE_WEIGHT_SECTION\n0 1 2\n1 0 3\n2 3 0\nEOF", header);
+        let upper = format!("{}EDGE_WEIGHT_FORMAT: UPPER_ROW\nEDGE_WEIGHT_SECTION\n1 2\n3\n", header);
+        let lower = format!("{}EDGE_WEIGHT_FORMAT: LOWER_DIAG_ROW\nEDGE_WEIGHT_SECTION\n0 1 0 2 3 0\n", header);
+
+        let full = parse_tsp(&full).unwrap();
+        assert_eq!(full.distances.get(1, 2), 3.0);
+        assert!(full.coordinates.is_none());
+        assert_eq!(parse_tsp(&upper).unwrap().distances, full.distances);
+        assert_eq!(parse_tsp(&lower).unwrap().distances, full.distances);
+    }
+
+    #[test]
+    fn test_parse_atsp() {
+        let text = "NAME: one-way\nTYPE: ATSP\nDIMENSION: 3\nEDGE_WEIGHT_TYPE: EXPLICIT\nEDGE_WEIGHT_FORMAT: FULL_MATRIX\n\
+                    EDGE_WEIGHT_SECTION\n9999 1 8\n8 9999 1\n1 8 9999\nEOF\n";
+        let instance = parse_tsp(text).unwrap();
+        assert_eq!(instance.distances.get(0, 1), 1.0);
+        assert_eq!(instance.distances.get(1, 0), 8.0);
+        assert_eq!(instance.distances.get(0, 0), 0.0);
+        assert!(!instance.distances.is_symmetric());
+    }
+
+    #[test]
+    fn test_parse_cvrp() {
+        let text = "NAME : tiny-n4\nTYPE : CVRP\nDIMENSION : 4\nEDGE_WEIGHT_TYPE : EUC_2D\nCAPACITY : 10\n\
+                    NODE_COORD_SECTION\n1 0 0\n2 3 0\n3 0 4\n4 3 4\n\
+                    DEMAND_SECTION\n1 0\n2 6\n3 5\n4 4\n\
+                    DEPOT_SECTION\n1\n-1\nEOF\n";
+        let instance = parse_tsp(text).unwrap();
+        assert_eq!(instance.capacity, Some(10.0));
+        assert_eq!(instance.demands, Some(vec![0.0, 6.0, 5.0, 4.0]));
+        assert_eq!(instance.depots, vec![0]);
+        assert_eq!(instance.distances.get(1, 2), 5.0);
+
+        let tsp = parse_tsp("DIMENSION : 1\nNODE_COORD_SECTION\n1 0 0\n").unwrap();
+        assert!(tsp.capacity.is_none() && tsp.demands.is_none() && tsp.depots.is_empty());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse_tsp("NAME: x\n").unwrap_err(), TsplibError::MissingDimension);
+        assert_eq!(
+            parse_tsp("DIMENSION: 3\nEDGE_WEIGHT_TYPE: EUC_3D\n").unwrap_err(),
+            TsplibError::UnsupportedEdgeWeightType("EUC_3D".to_string())
+        );
+        assert_eq!(
+            parse_tsp("DIMENSION: 3\nEDGE_WEIGHT_TYPE: EXPLICIT\nEDGE_WEIGHT_FORMAT: UPPER_ROW\nEDGE_WEIGHT_SECTION\n1 2\n")
+                .unwrap_err(),
+            TsplibError::WrongEntryCount { expected: 3, found: 2 }
+        );
+        assert_eq!(
+            parse_tsp("DIMENSION: 2\nNODE_COORD_SECTION\n1 0 0\n3 1 1\n").unwrap_err(),
+            TsplibError::InvalidNode(3)
+        );
+        assert_eq!(
+            parse_tsp("DIMENSION: 2\nNODE_COORD_SECTION\n1.5 0 0\n2 1 1\n").unwrap_err(),
+            TsplibError::InvalidNode(1)
+        );
+    }
+
+    #[test]
+    fn test_parse_tour() {
+        let text = "NAME : square4.opt.tour\nTYPE : TOUR\nDIMENSION : 4\nTOUR_SECTION\n1\n3 2\n4\n-1\nEOF\n";
+        let tour = parse_tour(text).unwrap();
+        assert_eq!(tour.name, "square4.opt.tour");
+        assert_eq!(tour.tour, vec![0, 2, 1, 3]);
+        assert_eq!(tour.route(), vec![0, 2, 1, 3, 0]);
+
+        assert_eq!(parse_tour("DIMENSION : 3\nTOUR_SECTION\n1 2 2 -1\n").unwrap_err(), TsplibError::InvalidTour);
+        assert_eq!(parse_tour("DIMENSION : 3\nTOUR_SECTION\n1 2.5 3 -1\n").unwrap_err(), TsplibError::InvalidNode(2));
+        assert_eq!(
+            parse_tour("DIMENSION : 3\nTOUR_SECTION\n1 2 -1\n").unwrap_err(),
+            TsplibError::WrongEntryCount { expected: 3, found: 2 }
+        );
+    }
+
+    #[test]
+    fn test_write_tour_round_trip() {
+        let text = write_tour("best", "length 14", &[0, 3, 1, 2, 0]);
+        assert!(text.contains("DIMENSION : 4\n"));
+        assert!(text.ends_with("3\n-1\nEOF\n"));
+        assert_eq!(parse_tour(&text).unwrap().route(), vec![0, 3, 1, 2, 0]);
+    }
+
+    #[test]
+    fn test_is_tour() {
+        assert!(is_tour(&[0, 3, 1, 2, 0], 4));
+        assert!(is_tour(&[2, 0, 1], 3));
+        // Routes that pass a city twice or skip one
+        assert!(!is_tour(&[0, 1, 0, 2, 0], 3));
+        assert!(!is_tour(&[0, 1, 0], 3));
+    }
+
+    #[test]
+    fn test_optimality_gap() {
+        assert_eq!(optimality_gap(7542.0, 7542.0), 0.0);
+        assert!((optimality_gap(7919.1, 7542.0) - 5.0).abs() < 1e-3);
+        assert_eq!(optimality_gap(f64::INFINITY, 7542.0), f64::INFINITY);
+    }
+}
@@ -3,11 +3,28 @@ pub mod geometry;
 pub mod rendering;
 pub mod simulation;
 pub mod input;
+pub mod io;
 
 use wasm_bindgen::prelude::*;
-use aco::colony::{Colony, ACOParameters};
-use geometry::city::City;
+use aco::colony::{Colony, ACOParameters, AcoVariant};
+use aco::local_search::{LocalSearchMode, LocalSearchOperator};
+use aco::update::StrategyRegistry;
+use aco::mtsp::{MultipleSalesmen, MultipleTsp, SalesmenObjective};
+use aco::precedence::Precedence;
+use aco::prize::{PrizeCollecting, PrizeCollectingTsp, PrizeGoal};
+use aco::problem::{Problem, Tsp};
+use aco::qap::Qap;
+use aco::vrp::{TimeWindows, VehicleRouting, VehicleRoutingProblem};
+use geometry::{
+    city::City,
+    distance::{calculate_total_distance, DistanceMatrix},
+    layout::{circle_layout, fit_to_canvas},
+    metric::DistanceMetric,
+    route::RouteMode,
+};
+use io::{qaplib, tsplib};
 use rendering::{CanvasRenderer, AnimationManager};
+use serde::Serialize;
 use web_sys::HtmlCanvasElement;
 
 #[global_allocator]
@@ -24,6 +41,7 @@ extern "C" {
     fn now() -> f64;
 }
 
+#[allow(unused_macros)]
 macro_rules! console_log {
     ($($t:tt)*) => (log(&format_args!($($t)*).to_string()))
 }
@@ -41,7 +59,28 @@ pub fn greet(name: &str) {
 #[wasm_bindgen]
 pub struct ACOEngine {
     colony: Option<Colony>,
+    parameters: ACOParameters,
+    strategies: StrategyRegistry,
+    update_strategy: Option<String>,
     cities: Vec<City>,
+    // Distances of a loaded instance; None means Euclidean distances between the cities
+    distances: Option<DistanceMatrix>,
+    // Raw positions the loaded distances are measured between, e.g. longitude/latitude; the
+    // cities only hold them scaled to the canvas
+    coordinates: Option<Vec<(f64, f64)>>,
+    // Known optimal tour of the current cities and its length
+    optimal_tour: Option<(Vec<usize>, f64)>,
+    // Demands, depot and capacity when solving a CVRP instead of a TSP
+    vehicle_routing: Option<VehicleRouting>,
+    salesmen: Option<MultipleSalesmen>,
+    precedence: Option<Precedence>,
+    prize_collecting: Option<PrizeCollecting>,
+    // A loaded quadratic assignment instance; the cities are its facilities and locations
+    qap: Option<Qap>,
+    // Named cost criteria besides the distances, e.g. tolls or travel times
+    criteria: Vec<(String, DistanceMatrix)>,
+    // Tour of the Pareto front drawn next to the best route
+    selected_pareto_tour: Option<usize>,
     renderer: Option<CanvasRenderer>,
     animation_manager: AnimationManager,
     is_running: bool,
@@ -53,7 +92,20 @@ impl ACOEngine {
     pub fn new() -> ACOEngine {
         ACOEngine {
             colony: None,
+            parameters: ACOParameters::default(),
+            strategies: StrategyRegistry::with_builtins(),
+            update_strategy: None,
             cities: Vec::new(),
+            distances: None,
+            coordinates: None,
+            optimal_tour: None,
+            vehicle_routing: None,
+            salesmen: None,
+            precedence: None,
+            prize_collecting: None,
+            qap: None,
+            criteria: Vec::new(),
+            selected_pareto_tour: None,
             renderer: None,
             animation_manager: AnimationManager::new(),
             is_running: false,
@@ -79,6 +131,23 @@ impl ACOEngine {
         let id = self.cities.len() as u32;
         let city = City::new(id, x, y);
         self.cities.push(city);
+        // A hand-placed city has no entry in a loaded instance, fall back to the drawn positions,
+        // which are pixels rather than longitude/latitude
+        self.distances = None;
+        self.coordinates = None;
+        if self.parameters.distance_metric.is_geographic() {
+            self.parameters.distance_metric = DistanceMetric::Euclidean;
+        }
+        self.optimal_tour = None;
+        self.vehicle_routing = None;
+        self.salesmen = None;
+        self.precedence = None;
+        self.prize_collecting = None;
+        self.qap = None;
+        self.criteria.clear();
+        if let Some(colony) = &mut self.colony {
+            colony.set_cities(self.cities.clone());
+        }
         
         // Re-render safely if canvas is initialized
         if self.renderer.is_some() {
@@ -91,6 +160,19 @@ impl ACOEngine {
     #[wasm_bindgen]
     pub fn clear_cities(&mut self) {
         self.cities.clear();
+        self.parameters.route_mode = RouteMode::Closed;
+        if self.parameters.distance_metric.is_geographic() {
+            self.parameters.distance_metric = DistanceMetric::Euclidean;
+        }
+        self.distances = None;
+        self.coordinates = None;
+        self.optimal_tour = None;
+        self.vehicle_routing = None;
+        self.salesmen = None;
+        self.precedence = None;
+        self.prize_collecting = None;
+        self.qap = None;
+        self.criteria.clear();
         self.colony = None;
         self.animation_manager.clear();
         
@@ -100,34 +182,561 @@ impl ACOEngine {
         }
     }
 
+    // Replaces the cities with a TSPLIB .tsp instance; returns the number of cities. Tours are
+    // measured in the instance's own metric while the cities are scaled to fit the canvas
+    #[wasm_bindgen]
+    pub fn load_tsplib(&mut self, text: &str) -> Result<usize, JsValue> {
+        let instance = tsplib::parse_tsp(text).map_err(|err| JsValue::from_str(&err.to_string()))?;
+        let points = match (instance.edge_weight_type, instance.coordinates) {
+            // Latitude grows northwards, longitude eastwards
+            (tsplib::EdgeWeightType::Geo, Some(points)) => points.iter().map(|&(lat, lon)| (lon, -lat)).collect(),
+            (_, Some(points)) => points.iter().map(|&(x, y)| (x, -y)).collect(),
+            (_, None) => circle_layout(instance.dimension),
+        };
+        // CVRP instances switch the engine to vehicle routing
+        let routing = match (instance.capacity, instance.demands) {
+            (Some(capacity), Some(demands)) => {
+                let depot = instance.depots.first().copied().unwrap_or(0);
+                Some(VehicleRouting::new(depot, demands, capacity).ok_or_else(|| {
+                    JsValue::from_str("every demand must fit into one vehicle")
+                })?)
+            }
+            _ => None,
+        };
+        self.load_instance(instance.distances, &points);
+        self.vehicle_routing = routing;
+        Ok(self.cities.len())
+    }
+
+    // Replaces the cities with a row-major n x n cost matrix, e.g. road distances from a routing
+    // engine. `coordinates` holds optional x, y pairs (longitude, latitude for map positions) used
+    // only to draw the cities; without them the cities are placed on a circle
+    #[wasm_bindgen]
+    pub fn load_distance_matrix(&mut self, matrix: Vec<f64>, coordinates: Option<Vec<f64>>) -> Result<usize, JsValue> {
+        let distances = DistanceMatrix::from_flat(matrix)
+            .ok_or_else(|| JsValue::from_str("expected a square matrix of non-negative costs"))?;
+        let points = match coordinates {
+            Some(coordinates) if coordinates.len() == 2 * distances.size() => {
+                coordinates.chunks(2).map(|point| (point[0], -point[1])).collect()
+            }
+            Some(coordinates) => {
+                return Err(JsValue::from_str(&format!(
+                    "expected {} coordinates, found {}",
+                    2 * distances.size(),
+                    coordinates.len()
+                )))
+            }
+            None => circle_layout(distances.size()),
+        };
+        self.load_instance(distances, &points);
+        Ok(self.cities.len())
+    }
+
+    // Replaces the cities with points given as x, y pairs, e.g. longitude, latitude for "geo" or
+    // "haversine". Tours are measured between the points in `metric` (see set_distance_metric)
+    // while the cities are scaled to fit the canvas
+    #[wasm_bindgen]
+    pub fn load_coordinates(&mut self, coordinates: Vec<f64>, metric: &str) -> Result<usize, JsValue> {
+        let metric = DistanceMetric::from_name(metric)
+            .ok_or_else(|| JsValue::from_str(&format!("unknown distance metric {}", metric)))?;
+        if !coordinates.len().is_multiple_of(2) || coordinates.len() < 6 {
+            return Err(JsValue::from_str("expected x, y pairs for at least three points"));
+        }
+        let points: Vec<(f64, f64)> = coordinates.chunks(2).map(|point| (point[0], point[1])).collect();
+        if let Some(index) = points.iter().position(|&point| !metric.accepts(point)) {
+            return Err(JsValue::from_str(&format!("point {} is not a longitude, latitude pair", index)));
+        }
+
+        let raw: Vec<City> = points.iter().enumerate().map(|(i, &(x, y))| City::new(i as u32, x, y)).collect();
+        // Latitude grows northwards, the canvas y axis downwards
+        let drawn: Vec<(f64, f64)> = points.iter().map(|&(x, y)| (x, -y)).collect();
+        self.load_instance(DistanceMatrix::with_metric(&raw, metric), &drawn);
+        self.parameters.distance_metric = metric;
+        self.coordinates = Some(points);
+        Ok(self.cities.len())
+    }
+
+    // Replaces the cities with a QAPLIB .dat instance; returns the number of facilities. The
+    // facilities are drawn on an inner circle and the locations on an outer one. The instance is
+    // always solved with MAX-MIN Ant System, which is what works best for assignment problems,
+    // whatever algorithm is selected (see get_algorithm)
+    #[wasm_bindgen]
+    pub fn load_qaplib(&mut self, text: &str) -> Result<usize, JsValue> {
+        let instance = qaplib::parse_qap(text).map_err(|err| JsValue::from_str(&err.to_string()))?;
+        let qap = Qap::new(instance.flow, instance.distance)
+            .ok_or_else(|| JsValue::from_str("expected at least two facilities and non-negative entries"))?;
+        let size = qap.num_facilities();
+        let facilities = circle_layout(size).into_iter().map(|(x, y)| (0.5 * x, 0.5 * y));
+        let points: Vec<(f64, f64)> = facilities.chain(circle_layout(size)).collect();
+        self.load_instance(DistanceMatrix::from_fn(2 * size, |_, _| 0.0), &points);
+        self.qap = Some(qap);
+        Ok(size)
+    }
+
+    // Loads a known (optimal) assignment of the loaded QAP instance from a QAPLIB .sln file;
+    // returns its cost
+    #[wasm_bindgen]
+    pub fn load_qap_solution(&mut self, text: &str) -> Result<f64, JsValue> {
+        let qap = self.qap.as_ref().ok_or_else(|| JsValue::from_str("no QAP instance is loaded"))?;
+        let solution = qaplib::parse_solution(text).map_err(|err| JsValue::from_str(&err.to_string()))?;
+        if solution.size != qap.num_facilities() {
+            return Err(JsValue::from_str(&format!(
+                "solution has {} facilities but the instance has {}",
+                solution.size,
+                qap.num_facilities()
+            )));
+        }
+
+        let cost = qap.assignment_cost(&solution.assignment);
+        self.optimal_tour = Some((qap.route_for(&solution.assignment), cost));
+
+        if self.renderer.is_some() {
+            self.safe_render();
+        }
+        Ok(cost)
+    }
+
+    // Loads a known (optimal) tour of the current cities from a TSPLIB .tour file; returns its length
+    #[wasm_bindgen]
+    pub fn load_optimal_tour(&mut self, text: &str) -> Result<f64, JsValue> {
+        let tour = tsplib::parse_tour(text).map_err(|err| JsValue::from_str(&err.to_string()))?;
+        if tour.tour.len() != self.cities.len() {
+            return Err(JsValue::from_str(&format!(
+                "tour has {} nodes but {} cities are loaded",
+                tour.tour.len(),
+                self.cities.len()
+            )));
+        }
+
+        let route = tour.route();
+        let distance = match &self.distances {
+            Some(distances) => distances.route_length(&route),
+            None => {
+                let cities: Vec<City> = tour.tour.iter().map(|&city| self.cities[city].clone()).collect();
+                calculate_total_distance(&cities, RouteMode::Closed, self.parameters.distance_metric)
+            }
+        };
+        self.optimal_tour = Some((route, distance));
+
+        if self.renderer.is_some() {
+            self.safe_render();
+        }
+        Ok(distance)
+    }
+
+    #[wasm_bindgen]
+    pub fn get_optimal_distance(&self) -> Option<f64> {
+        self.optimal_tour.as_ref().map(|(_, distance)| *distance)
+    }
+
+    // Percentage by which the best tour found exceeds the loaded optimal tour
+    #[wasm_bindgen]
+    pub fn get_optimality_gap(&self) -> Option<f64> {
+        let colony = self.colony.as_ref()?;
+        colony.best_route()?;
+        self.get_optimal_distance()
+            .map(|optimum| tsplib::optimality_gap(colony.best_length(), optimum))
+    }
+
+    // The best tour in TSPLIB .tour format, for other solvers; empty until a tour is found and for
+    // solutions that are no tour through every city, e.g. vehicle routes or QAP assignments
+    #[wasm_bindgen]
+    pub fn export_best_tour(&self, name: &str) -> String {
+        match self.colony.as_ref().and_then(|colony| colony.best_route().map(|route| (colony, route))) {
+            Some((colony, route)) if self.qap.is_none() && tsplib::is_tour(route, self.cities.len()) => {
+                tsplib::write_tour(name, &format!("Length {}", colony.best_length()), route)
+            }
+            _ => String::new(),
+        }
+    }
+
     #[wasm_bindgen]
     pub fn get_city_count(&self) -> usize {
         self.cities.len()
     }
 
     #[wasm_bindgen]
-    pub fn initialize_colony(&mut self, num_ants: usize, max_generations: usize, evaporation_rate: f64, alpha: f64, beta: f64) {
+    // Runs with the same cities and `seed` are identical; without a seed a random one is
+    // picked and can be read back through get_seed. Fails, keeping the previous colony, if the
+    // vehicle routing, salesmen, prize or criteria settings do not fit the cities, or if an update
+    // rule override other than "mmas" is set for a QAP instance
+    pub fn initialize_colony(&mut self, num_ants: usize, max_generations: usize, evaporation_rate: f64, alpha: f64, beta: f64, seed: Option<u32>) -> Result<(), JsValue> {
         if self.cities.len() < 3 {
-            return;
+            return Ok(());
         }
 
-        let parameters = ACOParameters {
+        let mut parameters = ACOParameters {
             num_ants,
             max_generations,
             evaporation_rate,
             alpha,
             beta,
             initial_pheromone: 1.0,
+            seed: Some(seed.unwrap_or_else(rand::random) as u64),
+            ..self.parameters.clone()
         };
+        // Only for this colony, the selected algorithm applies again once the instance is replaced
+        if self.qap.is_some() {
+            if self.update_strategy.as_ref().is_some_and(|name| name != "mmas") {
+                return Err(JsValue::from_str(
+                    "QAP instances are solved with MAX-MIN Ant System (\"mmas\"), clear the update rule override",
+                ));
+            }
+            parameters.variant = AcoVariant::MaxMin;
+        }
+        let strategy = self
+            .update_strategy
+            .as_ref()
+            .and_then(|name| self.strategies.create(name, &parameters));
 
-        self.colony = Some(Colony::new(self.cities.clone(), parameters));
+        let mut colony = match (&self.qap, &self.distances) {
+            (Some(qap), _) => Colony::with_problem(self.cities.clone(), Box::new(qap.clone()), parameters),
+            (None, Some(distances)) => Colony::with_distance_matrix(self.cities.clone(), distances.clone(), parameters),
+            (None, None) => Colony::new(self.cities.clone(), parameters),
+        };
+        // Taken as long as no criteria are set, which only happens below
+        if let Some(strategy) = strategy {
+            colony.set_update_strategy(strategy);
+        }
+        // The tour variants would replace an assignment problem
+        if self.qap.is_none() {
+            let problem = self.build_problem(colony.distances()).map_err(|err| JsValue::from_str(&err))?;
+            // Built over the colony's own distances, so it always fits
+            colony.set_problem(problem);
+            if !self.criteria.is_empty() && !colony.supports_criteria() {
+                return Err(JsValue::from_str(
+                    "several cost criteria need Ant System (\"as\") without local search or polishing",
+                ));
+            }
+            if !colony.set_criteria(self.criteria.iter().map(|(_, costs)| costs.clone()).collect()) {
+                return Err(JsValue::from_str("the cost criteria do not match the cities"));
+            }
+        }
+        self.selected_pareto_tour = None;
         
-        // Initialize ant animations
+        // Initialize ant animations where the problem starts its ants
         self.animation_manager.clear();
         for i in 0..num_ants {
-            let start_city = &self.cities[i % self.cities.len()];
+            let start_city = &self.cities[colony.problem().new_ant(i).current_city()];
             self.animation_manager.add_ant(i as u32, start_city.x(), start_city.y());
         }
+        self.colony = Some(colony);
+        Ok(())
+    }
+
+    // Adds a cost criterion besides the distances as a row-major n x n matrix over the current
+    // cities; from the next initialize_colony call on the colony searches for the tours no other
+    // tour beats in every criterion. Returns the number of criteria, distances included. The
+    // search runs Ant System with an update of its own: initialize_colony fails while another
+    // algorithm or update rule, local search or polishing is selected
+    #[wasm_bindgen]
+    pub fn add_cost_criterion(&mut self, name: &str, matrix: Vec<f64>) -> Result<usize, JsValue> {
+        let costs = DistanceMatrix::from_flat(matrix)
+            .ok_or_else(|| JsValue::from_str("expected a square matrix of non-negative costs"))?;
+        if costs.size() != self.cities.len() {
+            return Err(JsValue::from_str(&format!(
+                "matrix has {} rows but {} cities are loaded",
+                costs.size(),
+                self.cities.len()
+            )));
+        }
+        self.criteria.push((name.to_string(), costs));
+        Ok(self.criteria.len() + 1)
+    }
+
+    // Back to distance as the only criterion
+    #[wasm_bindgen]
+    pub fn clear_cost_criteria(&mut self) {
+        self.criteria.clear();
+    }
+
+    // "distance" followed by the names of the added criteria, in the order of the Pareto costs
+    #[wasm_bindgen]
+    pub fn get_criterion_names(&self) -> Vec<String> {
+        std::iter::once("distance".to_string()).chain(self.criteria.iter().map(|(name, _)| name.clone())).collect()
+    }
+
+    // Selects the algorithm used by the next initialize_colony call ("as", "mmas", "acs", "elitist", "rank");
+    // a loaded QAP instance is solved with "mmas" regardless
+    #[wasm_bindgen]
+    pub fn set_algorithm(&mut self, name: &str) -> bool {
+        match AcoVariant::from_name(name) {
+            Some(variant) => {
+                self.parameters.variant = variant;
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Overrides the pheromone update rule of the selected algorithm with a registered
+    // strategy ("as", "elitist", "rank", "mmas", "acs", "best-worst", "hyper-cube");
+    // an empty name restores the algorithm's own rule
+    #[wasm_bindgen]
+    pub fn set_update_strategy(&mut self, name: &str) -> bool {
+        if name.is_empty() {
+            self.update_strategy = None;
+            return true;
+        }
+        if self.strategies.create(name, &self.parameters).is_none() {
+            return false;
+        }
+        self.update_strategy = Some(name.to_string());
+        true
+    }
+
+    // Local search on the constructed tours: "off", "all" or "best" (iteration best only)
+    #[wasm_bindgen]
+    pub fn set_local_search(&mut self, mode: &str) -> bool {
+        match LocalSearchMode::from_name(mode) {
+            Some(mode) => {
+                self.parameters.local_search = mode;
+                true
+            }
+            None => false,
+        }
+    }
+
+    // "2-opt", "or-opt", "2-opt+or-opt" or "3-opt"
+    #[wasm_bindgen]
+    pub fn set_local_search_operator(&mut self, name: &str) -> bool {
+        match LocalSearchOperator::from_name(name) {
+            Some(operator) => {
+                self.parameters.local_search_operator = operator;
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Chained Lin-Kernighan on the best tour every `interval` generations (0 = never)
+    // and/or once the run is complete
+    #[wasm_bindgen]
+    pub fn set_polishing(&mut self, interval: usize, on_complete: bool) {
+        self.parameters.polish_interval = interval;
+        self.parameters.polish_on_complete = on_complete;
+    }
+
+    // Polishes the current best tour immediately; returns the distance saved
+    #[wasm_bindgen]
+    pub fn polish_best_route(&mut self) -> f64 {
+        if let Some(colony) = &mut self.colony {
+            colony.polish_best_route()
+        } else {
+            0.0
+        }
+    }
+
+    // Metric for hand-placed cities and points from load_coordinates: "euclidean", "rounded",
+    // "ceil", "manhattan", "chebyshev", "att", "geo" or "haversine". The geographic metrics read x
+    // as longitude and y as latitude, so they are refused for cities placed on the canvas. False,
+    // changing nothing, for instances loaded with their own distances
+    #[wasm_bindgen]
+    pub fn set_distance_metric(&mut self, name: &str) -> bool {
+        let Some(metric) = DistanceMetric::from_name(name) else {
+            return false;
+        };
+        match &self.coordinates {
+            Some(points) if points.iter().all(|&point| metric.accepts(point)) => {
+                let raw: Vec<City> = points.iter().enumerate().map(|(i, &(x, y))| City::new(i as u32, x, y)).collect();
+                self.distances = Some(DistanceMatrix::with_metric(&raw, metric));
+            }
+            Some(_) => return false,
+            // A loaded instance brings its own distances
+            None if self.distances.is_some() => return false,
+            None if metric.is_geographic() => return false,
+            None => {}
+        }
+        self.parameters.distance_metric = metric;
+        self.optimal_tour = None;
+        true
+    }
+
+    // "closed" tours, "open" paths, "fixed-start" paths from `start` or "fixed-start-end" paths
+    // from `start` to `end`; the cities must already exist. False, keeping the current mode, if
+    // the endpoints contradict the precedence constraints or the tours start from a depot
+    // (vehicle routing, salesmen or prizes)
+    #[wasm_bindgen]
+    pub fn set_route_mode(&mut self, mode: &str, start: Option<u32>, end: Option<u32>) -> bool {
+        let fits_precedence = |route_mode| self.precedence.as_ref().is_none_or(|precedence| precedence.fits(route_mode));
+        match RouteMode::from_parts(mode, start.map(|s| s as usize), end.map(|e| e as usize)) {
+            // Tours from a depot always return to it
+            Some(route_mode) if !route_mode.is_closed() && self.depot().is_some() => false,
+            Some(route_mode) if route_mode.fits(self.cities.len()) && fits_precedence(route_mode) => {
+                self.parameters.route_mode = route_mode;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // Solves a capacitated vehicle routing problem over the current cities: one demand per city
+    // (the depot's is ignored) and the capacity of every vehicle. Time windows already set are
+    // kept, and routes are closed tours from the depot again
+    #[wasm_bindgen]
+    pub fn set_vehicle_routing(&mut self, demands: Vec<f64>, depot: u32, capacity: f64) -> bool {
+        if demands.len() != self.cities.len() {
+            return false;
+        }
+        // Time windows set earlier stay, so the call fails if they no longer fit
+        let windows = self.vehicle_routing.as_ref().and_then(|routing| routing.time_windows().cloned());
+        let routing = VehicleRouting::new(depot as usize, demands, capacity).and_then(|routing| match windows {
+            Some(windows) => routing.with_time_windows(windows),
+            None => Some(routing),
+        });
+        match routing {
+            Some(routing) => {
+                self.vehicle_routing = Some(routing);
+                self.salesmen = None;
+                self.parameters.route_mode = RouteMode::Closed;
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Back to the travelling salesman problem
+    #[wasm_bindgen]
+    pub fn clear_vehicle_routing(&mut self) {
+        self.vehicle_routing = None;
+    }
+
+    // Customer time windows and service durations, one entry per city. Without vehicle routing
+    // the cities are served from city 0 by uncapacitated vehicles
+    #[wasm_bindgen]
+    pub fn set_time_windows(&mut self, ready: Vec<f64>, due: Vec<f64>, service: Vec<f64>) -> bool {
+        let routing = match &self.vehicle_routing {
+            Some(routing) => Some(routing.clone()),
+            None => VehicleRouting::new(0, vec![0.0; self.cities.len()], f64::INFINITY),
+        };
+        let with_windows = routing
+            .zip(TimeWindows::new(ready, due, service))
+            .and_then(|(routing, windows)| routing.with_time_windows(windows));
+        match with_windows {
+            Some(routing) => {
+                self.vehicle_routing = Some(routing);
+                self.salesmen = None;
+                self.parameters.route_mode = RouteMode::Closed;
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Splits the cities among `count` salesmen who all start and end at `depot`, minimising the
+    // total distance ("total") or the longest tour ("minmax"); routes are closed tours again
+    #[wasm_bindgen]
+    pub fn set_salesmen(&mut self, count: u32, depot: u32, objective: &str) -> bool {
+        let salesmen = SalesmenObjective::from_name(objective).and_then(|objective| {
+            MultipleSalesmen::new(depot as usize, count as usize, objective, self.cities.len())
+        });
+        match salesmen {
+            Some(salesmen) => {
+                self.salesmen = Some(salesmen);
+                self.vehicle_routing = None;
+                self.parameters.route_mode = RouteMode::Closed;
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Back to a single salesman
+    #[wasm_bindgen]
+    pub fn clear_salesmen(&mut self) {
+        self.salesmen = None;
+    }
+
+    // Precedence constraints as flat [before, after, before, after, ...] city pairs; false if
+    // they name unknown cities, form a cycle or contradict the fixed route endpoints. They only
+    // apply to single tours: initialize_colony fails while vehicle routing, several salesmen or a
+    // prize goal are set up as well
+    #[wasm_bindgen]
+    pub fn set_precedence(&mut self, pairs: Vec<u32>) -> bool {
+        if !pairs.len().is_multiple_of(2) {
+            return false;
+        }
+        let pairs: Vec<(usize, usize)> = pairs.chunks(2).map(|pair| (pair[0] as usize, pair[1] as usize)).collect();
+        match Precedence::new(self.cities.len(), &pairs) {
+            Some(precedence) if precedence.fits(self.parameters.route_mode) => {
+                self.precedence = Some(precedence);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn clear_precedence(&mut self) {
+        self.precedence = None;
+    }
+
+    // What each city is worth to prize-collecting and orienteering tours, used from the next
+    // initialize_colony call on; false unless there is one finite, non-negative prize per city
+    #[wasm_bindgen]
+    pub fn set_prizes(&mut self, prizes: Vec<f64>) -> bool {
+        if prizes.len() != self.cities.len() || !prizes.iter().all(|&prize| prize.is_finite() && prize >= 0.0) {
+            return false;
+        }
+        for (city, prize) in self.cities.iter_mut().zip(prizes) {
+            city.set_prize(Some(prize));
+        }
+        true
+    }
+
+    // Tours from `depot` that may skip cities: "orienteering" collects as much prize as `budget`
+    // allows, "prize-collecting" trades the tour length against the prizes of skipped cities;
+    // routes are closed tours again
+    #[wasm_bindgen]
+    pub fn set_prize_goal(&mut self, goal: &str, depot: u32, budget: Option<f64>) -> bool {
+        match PrizeGoal::from_parts(goal, budget) {
+            Some(goal) if (depot as usize) < self.cities.len() => {
+                self.prize_collecting = Some(PrizeCollecting { depot: depot as usize, goal });
+                self.parameters.route_mode = RouteMode::Closed;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // Back to tours through every city
+    #[wasm_bindgen]
+    pub fn clear_prize_goal(&mut self) {
+        self.prize_collecting = None;
+    }
+
+    // How strongly ants prefer customers whose time window is about to close
+    #[wasm_bindgen]
+    pub fn set_urgency_weight(&mut self, weight: f64) {
+        self.parameters.urgency_weight = weight.max(0.0);
+    }
+
+    // Number of nearest cities an ant chooses from at each step (0 = all cities)
+    #[wasm_bindgen]
+    pub fn set_candidate_list_size(&mut self, size: usize) {
+        self.parameters.candidate_list_size = size;
+    }
+
+    // The algorithm the colony runs, which is "mmas" for QAP instances whatever was selected
+    #[wasm_bindgen]
+    pub fn get_algorithm(&self) -> String {
+        match &self.colony {
+            Some(colony) => colony.variant().name().to_string(),
+            None => self.parameters.variant.name().to_string(),
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn get_update_strategy(&self) -> String {
+        match &self.colony {
+            Some(colony) => colony.update_strategy().name().to_string(),
+            None => self.update_strategy.clone().unwrap_or_default(),
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn get_update_strategy_names(&self) -> Vec<String> {
+        self.strategies.names()
     }
 
     #[wasm_bindgen]
@@ -173,15 +782,41 @@ impl ACOEngine {
         self.animation_manager.set_animation_speed(speed);
     }
 
+    // Length of the best solution's routes; the prize of prize tours is in get_solution_summary
     #[wasm_bindgen]
     pub fn get_best_distance(&self) -> f64 {
         if let Some(colony) = &self.colony {
-            colony.best_distance()
+            colony.best_length()
         } else {
             f64::INFINITY
         }
     }
 
+    // Distance saved by local search in the last generation
+    #[wasm_bindgen]
+    pub fn get_local_search_gain(&self) -> f64 {
+        if let Some(colony) = &self.colony {
+            colony.local_search_gain()
+        } else {
+            0.0
+        }
+    }
+
+    // Distance removed from the best tour by Lin-Kernighan polishing so far
+    #[wasm_bindgen]
+    pub fn get_polish_gain(&self) -> f64 {
+        if let Some(colony) = &self.colony {
+            colony.polish_gain()
+        } else {
+            0.0
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn get_seed(&self) -> Option<u32> {
+        self.colony.as_ref().map(|colony| colony.seed() as u32)
+    }
+
     #[wasm_bindgen]
     pub fn get_generation(&self) -> usize {
         if let Some(colony) = &self.colony {
@@ -191,6 +826,8 @@ impl ACOEngine {
         }
     }
 
+    // The best solution as one array; routes from a depot are joined into a giant tour that
+    // passes the depot between them, see get_best_routes
     #[wasm_bindgen]
     pub fn get_best_route(&self) -> Vec<u32> {
         if let Some(colony) = &self.colony {
@@ -204,6 +841,94 @@ impl ACOEngine {
         }
     }
 
+    // The best solution as one array of cities per vehicle or salesman, each starting and ending
+    // at the depot
+    #[wasm_bindgen]
+    pub fn get_best_routes(&self) -> Result<JsValue, JsValue> {
+        let routes: Vec<Vec<u32>> = match &self.colony {
+            Some(colony) => colony
+                .best_vehicle_routes()
+                .iter()
+                .map(|route| route.iter().map(|&x| x as u32).collect())
+                .collect(),
+            None => Vec::new(),
+        };
+        serde_wasm_bindgen::to_value(&routes).map_err(JsValue::from)
+    }
+
+    // What the problem reports about the best solution, null for a plain TSP or before the first
+    // generation: { schedules, time_window_violations } for vehicle routing, { objective,
+    // total_distance, longest_tour, tours } for several salesmen, { collected, length, skipped }
+    // for prize collecting, { precedence_violations } with precedence constraints and
+    // { assignment } for a QAP instance
+    #[wasm_bindgen]
+    pub fn get_solution_summary(&self) -> Result<JsValue, JsValue> {
+        let summary = self.colony.as_ref().map(|colony| colony.best_summary()).unwrap_or_default();
+        // As plain objects rather than JS Maps
+        summary.serialize(&serde_wasm_bindgen::Serializer::json_compatible()).map_err(JsValue::from)
+    }
+
+    // The non-dominated tours found so far as { route, costs } sorted by distance, with one cost
+    // per criterion in the order of get_criterion_names; empty without further criteria
+    #[wasm_bindgen]
+    pub fn get_pareto_front(&self) -> Result<JsValue, JsValue> {
+        let front = self.colony.as_ref().map(|colony| colony.pareto_front()).unwrap_or_default();
+        serde_wasm_bindgen::to_value(&front).map_err(JsValue::from)
+    }
+
+    // Draws the tour at `index` of the Pareto front on top of the best route (None hides it);
+    // false if there is no such tour
+    #[wasm_bindgen]
+    pub fn select_pareto_tour(&mut self, index: Option<u32>) -> bool {
+        let size = self.colony.as_ref().map_or(0, |colony| colony.pareto_front().len());
+        match index {
+            Some(index) if index as usize >= size => false,
+            _ => {
+                self.selected_pareto_tour = index.map(|index| index as usize);
+                if self.renderer.is_some() {
+                    self.safe_render();
+                }
+                true
+            }
+        }
+    }
+
+    // Plots the Pareto front on `canvas` with criterion `x` against criterion `y` (indices into
+    // get_criterion_names), highlighting the selected tour
+    #[wasm_bindgen]
+    pub fn render_pareto_front(&self, canvas: HtmlCanvasElement, x: usize, y: usize) -> Result<(), JsValue> {
+        let names = self.get_criterion_names();
+        if x >= names.len() || y >= names.len() {
+            return Err(JsValue::from_str(&format!("expected criteria below {}", names.len())));
+        }
+        let front = self.colony.as_ref().map(|colony| colony.pareto_front()).unwrap_or_default();
+        // Plotted by increasing x so that the staircase runs left to right
+        let mut order: Vec<usize> = (0..front.len()).collect();
+        order.sort_by(|&a, &b| front[a].costs[x].total_cmp(&front[b].costs[x]));
+        let points: Vec<f64> = order.iter().flat_map(|&i| [front[i].costs[x], front[i].costs[y]]).collect();
+        let selected = self
+            .selected_pareto_tour
+            .and_then(|selected| order.iter().position(|&i| i == selected))
+            .map(|position| position as u32);
+
+        CanvasRenderer::new(canvas)?.draw_pareto_front(&points, selected, &names[x], &names[y]);
+        Ok(())
+    }
+
+    // Number of stops of the best solution served after their due time
+    #[wasm_bindgen]
+    pub fn get_time_window_violations(&self) -> usize {
+        self.colony.as_ref().map_or(0, |colony| colony.best_violations())
+    }
+
+    #[wasm_bindgen]
+    pub fn get_vehicle_count(&self) -> usize {
+        match &self.colony {
+            Some(colony) if colony.depot().is_some() => colony.best_vehicle_routes().len(),
+            _ => 0,
+        }
+    }
+
     #[wasm_bindgen]
     pub fn is_complete(&self) -> bool {
         if let Some(colony) = &self.colony {
@@ -218,18 +943,32 @@ impl ACOEngine {
         self.is_running
     }
 
-    fn get_pheromone_matrix_flat(&self) -> Vec<f64> {
-        if let Some(_colony) = &self.colony {
-            // This would need to be implemented in Colony to expose pheromone matrix
-            // For now, return empty vec
-            Vec::new()
+    // Row-major n x n pheromone matrix, returned to JS as a Float64Array
+    #[wasm_bindgen]
+    pub fn get_pheromone_matrix_flat(&self) -> Vec<f64> {
+        if let Some(colony) = &self.colony {
+            colony.pheromone_matrix().to_flat()
         } else {
             Vec::new()
         }
     }
 
-    fn get_max_pheromone(&self) -> f64 {
-        1.0 // Default max pheromone value
+    #[wasm_bindgen]
+    pub fn get_max_pheromone(&self) -> f64 {
+        if let Some(colony) = &self.colony {
+            colony.pheromone_matrix().max()
+        } else {
+            0.0
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn get_min_pheromone(&self) -> f64 {
+        if let Some(colony) = &self.colony {
+            colony.pheromone_matrix().min()
+        } else {
+            0.0
+        }
     }
 
     fn cities_to_json(&self) -> String {
@@ -254,9 +993,15 @@ impl ACOEngine {
         if let Some(renderer) = &self.renderer {
             renderer.clear();
             
-            // Draw cities first - this is always safe
-            for city in &self.cities {
-                renderer.draw_city(city, false);
+            // Draw cities first - this is always safe; the depot or fixed route endpoints are highlighted
+            let route_mode = self.parameters.route_mode;
+            let depot = self.depot();
+            for (i, city) in self.cities.iter().enumerate() {
+                let endpoint = match depot {
+                    Some(depot) => depot == i,
+                    None => route_mode.start() == Some(i) || route_mode.end() == Some(i),
+                };
+                renderer.draw_city(city, endpoint);
             }
             
             // Only draw complex elements if we have a proper colony setup
@@ -264,14 +1009,36 @@ impl ACOEngine {
                 // Draw pheromone trails and best route only if we have enough cities
                 if self.cities.len() >= 3 {
                     let pheromone_matrix = self.get_pheromone_matrix_flat();
-                    let max_pheromone = self.get_max_pheromone();
                     let cities_json = self.cities_to_json();
-                    renderer.draw_pheromone_trail_simple(&cities_json, &pheromone_matrix, max_pheromone);
+                    renderer.draw_pheromone_trail_simple(
+                        &cities_json,
+                        &pheromone_matrix,
+                        colony.pheromone_matrix().min(),
+                        colony.pheromone_matrix().max(),
+                    );
+
+                    if let Some((optimal_route, _)) = &self.optimal_tour {
+                        let route: Vec<u32> = optimal_route.iter().map(|&x| x as u32).collect();
+                        match &self.qap {
+                            Some(_) => Self::draw_assignment(renderer, &cities_json, &route, "#94a3b8", 1.5),
+                            None => renderer.draw_route_simple(&cities_json, &route, "#94a3b8", 1.5),
+                        }
+                    }
 
                     // Draw best route if available
                     if let Some(best_route) = colony.best_route() {
                         let route: Vec<u32> = best_route.iter().map(|&x| x as u32).collect();
-                        renderer.draw_route_simple(&cities_json, &route, "#ef4444", 3.0);
+                        match (&self.qap, colony.depot()) {
+                            (Some(_), _) => Self::draw_assignment(renderer, &cities_json, &route, "#ef4444", 3.0),
+                            (None, Some(depot)) => renderer.draw_vehicle_routes(&cities_json, &route, depot as u32, 3.0),
+                            (None, None) => renderer.draw_route_simple(&cities_json, &route, "#ef4444", 3.0),
+                        }
+                    }
+
+                    // The trade-off picked from the Pareto front
+                    if let Some(tour) = self.selected_pareto_tour.and_then(|index| colony.pareto_front().into_iter().nth(index)) {
+                        let route: Vec<u32> = tour.route.iter().map(|&x| x as u32).collect();
+                        renderer.draw_route_simple(&cities_json, &route, "#a855f7", 2.0);
                     }
                 }
                 
@@ -291,5 +1058,176 @@ impl ACOEngine {
     }
 }
 
+impl ACOEngine {
+    // The problem the colony solves over `distances` with the current settings; fails if they do
+    // not fit the cities or if they set up problems that exclude each other
+    fn build_problem(&self, distances: &DistanceMatrix) -> Result<Box<dyn Problem>, String> {
+        fn boxed<P: Problem + 'static>(problem: Option<P>, error: &str) -> Result<Box<dyn Problem>, String> {
+            problem.map(|problem| Box::new(problem) as Box<dyn Problem>).ok_or_else(|| error.to_string())
+        }
+
+        // Vehicles and salesmen visit every city, prize-collecting tours may skip some
+        if self.prize_collecting.is_some() && (self.vehicle_routing.is_some() || self.salesmen.is_some()) {
+            return Err("a prize goal cannot be combined with vehicle routing or several salesmen".to_string());
+        }
+        // Only single tours are ordered by the constraints
+        if self.precedence.is_some()
+            && (self.vehicle_routing.is_some() || self.salesmen.is_some() || self.prize_collecting.is_some())
+        {
+            return Err("precedence constraints cannot be combined with vehicle routing, several salesmen or a prize goal"
+                .to_string());
+        }
+        let distances = distances.clone();
+        let tsp = Tsp::new(distances.clone(), self.parameters.route_mode);
+        match (&self.vehicle_routing, &self.salesmen, self.prize_collecting, &self.precedence) {
+            (Some(routing), ..) => boxed(
+                VehicleRoutingProblem::new(routing.clone(), distances, self.parameters.urgency_weight),
+                "the demands or time windows do not match the cities",
+            ),
+            (None, Some(salesmen), ..) => boxed(
+                MultipleTsp::new(salesmen.clone(), distances),
+                "the salesmen were set up for a different number of cities",
+            ),
+            (None, None, Some(settings), _) => boxed(
+                PrizeCollectingTsp::new(settings, &self.cities, distances),
+                "the prize goal's depot is not one of the cities",
+            ),
+            (None, None, None, Some(precedence)) => boxed(
+                tsp.with_precedence(precedence.clone()),
+                "the precedence constraints do not fit the cities or the route endpoints",
+            ),
+            (None, None, None, None) => Ok(Box::new(tsp)),
+        }
+    }
+
+    // Where the vehicle routing, salesmen or prize tours start and end; they ignore the route mode
+    fn depot(&self) -> Option<usize> {
+        match (&self.vehicle_routing, &self.salesmen, self.prize_collecting) {
+            (Some(routing), _, _) => Some(routing.depot()),
+            (None, Some(salesmen), _) => Some(salesmen.depot()),
+            (None, None, settings) => settings.map(|settings| settings.depot),
+        }
+    }
+
+    fn load_instance(&mut self, distances: DistanceMatrix, points: &[(f64, f64)]) {
+        let (width, height) = match &self.renderer {
+            Some(renderer) => (renderer.get_width() as f64, renderer.get_height() as f64),
+            None => (800.0, 600.0),
+        };
+        self.cities = fit_to_canvas(points, width, height);
+        self.parameters.route_mode = RouteMode::Closed;
+        self.vehicle_routing = None;
+        self.salesmen = None;
+        self.precedence = None;
+        self.prize_collecting = None;
+        self.qap = None;
+        self.criteria.clear();
+        self.distances = Some(distances);
+        self.coordinates = None;
+        self.optimal_tour = None;
+        self.colony = None;
+        self.animation_manager.clear();
+
+        if self.renderer.is_some() {
+            self.safe_render();
+        }
+    }
+
+    // One line from every facility to its location
+    fn draw_assignment(renderer: &CanvasRenderer, cities_json: &str, route: &[u32], color: &str, width: f64) {
+        for pair in route.chunks(2) {
+            renderer.draw_route_simple(cities_json, pair, color, width);
+        }
+    }
+
+    // Lets Rust callers embedding the engine add their own rules to the JS selector
+    pub fn strategy_registry_mut(&mut self) -> &mut StrategyRegistry {
+        &mut self.strategies
+    }
+}
+
+impl Default for ACOEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 // Export public types for external use
-// Note: These are already imported above, so we don't need to re-export them
\ No newline at end of file
+// Note: These are already imported above, so we don't need to re-export them
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SQUARE: &str = "NAME: square\nDIMENSION: 4\nEDGE_WEIGHT_TYPE: EUC_2D\nNODE_COORD_SECTION\n1 0 0\n2 3 0\n3 3 4\n4 0 4\nEOF\n";
+    const SQUARE_TOUR: &str = "DIMENSION: 4\nTOUR_SECTION\n1 2 3 4 -1\n";
+
+    fn square_engine() -> ACOEngine {
+        let mut engine = ACOEngine::new();
+        for (x, y) in [(0.0, 0.0), (30.0, 0.0), (30.0, 40.0), (0.0, 40.0)] {
+            engine.add_city(x, y);
+        }
+        engine
+    }
+
+    #[test]
+    fn test_vehicle_routing_keeps_time_windows() {
+        let mut engine = square_engine();
+        assert!(engine.set_route_mode("open", None, None));
+        assert!(engine.set_time_windows(vec![0.0; 4], vec![100.0; 4], vec![1.0; 4]));
+        assert_eq!(engine.parameters.route_mode, RouteMode::Closed);
+        assert!(engine.set_vehicle_routing(vec![0.0, 1.0, 1.0, 1.0], 0, 2.0));
+        assert!(engine.vehicle_routing.as_ref().unwrap().time_windows().is_some());
+
+        // Routes from a depot are always closed
+        assert!(!engine.set_route_mode("fixed-start", Some(1), None));
+        assert!(engine.set_route_mode("closed", None, None));
+        engine.clear_vehicle_routing();
+        assert!(engine.set_route_mode("fixed-start", Some(1), None));
+    }
+
+    #[test]
+    fn test_qap_runs_max_min() {
+        let mut engine = ACOEngine::new();
+        engine.load_qaplib("3\n0 5 2\n5 0 3\n2 3 0\n0 8 15\n8 0 13\n15 13 0\n").unwrap();
+        assert!(engine.set_algorithm("acs"));
+        assert_eq!(engine.get_algorithm(), "acs");
+        engine.initialize_colony(3, 2, 0.1, 1.0, 2.0, Some(1)).unwrap();
+        assert_eq!(engine.get_algorithm(), "mmas");
+        assert_eq!(engine.parameters.variant, AcoVariant::AntColonySystem);
+    }
+
+    #[test]
+    fn test_distance_metric_of_hand_placed_cities() {
+        let mut engine = ACOEngine::new();
+        engine.add_city(0.0, 0.0);
+        engine.add_city(30.0, 40.0);
+        assert!(!engine.set_distance_metric("haversine"));
+        assert!(engine.set_distance_metric("manhattan"));
+        assert_eq!(engine.parameters.distance_metric, DistanceMetric::Manhattan);
+    }
+
+    #[test]
+    fn test_distance_metric_of_loaded_instance() {
+        let mut engine = ACOEngine::new();
+        engine.load_tsplib(SQUARE).unwrap();
+        assert_eq!(engine.load_optimal_tour(SQUARE_TOUR).unwrap(), 14.0);
+        // The file's own metric keeps applying, and so does the optimal tour measured in it
+        assert!(!engine.set_distance_metric("manhattan"));
+        assert_eq!(engine.parameters.distance_metric, DistanceMetric::Euclidean);
+        assert_eq!(engine.get_optimal_distance(), Some(14.0));
+    }
+
+    #[test]
+    fn test_distance_metric_of_coordinates() {
+        let mut engine = ACOEngine::new();
+        // Paris, London and Berlin as longitude, latitude
+        engine.load_coordinates(vec![2.3522, 48.8566, -0.1276, 51.5072, 13.405, 52.52], "haversine").unwrap();
+        let paris_london = engine.distances.as_ref().unwrap().get(0, 1);
+        assert!((paris_london - 344.0).abs() < 2.0);
+
+        assert!(engine.set_distance_metric("euclidean"));
+        assert!(engine.distances.as_ref().unwrap().get(0, 1) < 5.0);
+        assert!(engine.set_distance_metric("geo"));
+    }
+}
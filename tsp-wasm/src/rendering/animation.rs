@@ -4,6 +4,7 @@ use std::collections::HashMap;
 #[wasm_bindgen]
 #[derive(Debug, Clone)]
 pub struct AntAnimation {
+    #[allow(dead_code)]
     id: u32,
     current_x: f64,
     current_y: f64,
@@ -158,7 +159,7 @@ impl AnimationManager {
 
     #[wasm_bindgen]
     pub fn set_animation_speed(&mut self, speed: f64) {
-        self.animation_speed = speed.max(0.1).min(10.0);
+        self.animation_speed = speed.clamp(0.1, 10.0);
         for animation in self.ant_animations.values_mut() {
             animation.speed = self.animation_speed;
         }
@@ -176,6 +177,12 @@ impl AnimationManager {
     }
 }
 
+impl Default for AnimationManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -194,11 +201,12 @@ mod tests {
         animation.set_target(10.0, 10.0);
         assert!(!animation.is_complete());
         
-        animation.update(0.5); // 0.5 seconds
+        animation.update(0.25); // 0.25 seconds
         assert!(!animation.is_complete());
         
-        animation.update(0.5); // Another 0.5 seconds (total 1.0s at speed 2.0)
+        animation.update(0.25); // Another 0.25 seconds (a move takes 1 / speed = 0.5s)
         assert!(animation.is_complete());
+        assert_eq!(animation.get_position(), vec![10.0, 10.0]);
     }
 
     #[test]
@@ -2,6 +2,11 @@ use wasm_bindgen::prelude::*;
 use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
 use crate::geometry::city::City;
 
+// Colours cycled through when drawing one route per vehicle
+const VEHICLE_COLORS: [&str; 8] = [
+    "#ef4444", "#3b82f6", "#22c55e", "#f59e0b", "#a855f7", "#14b8a6", "#ec4899", "#84cc16",
+];
+
 #[wasm_bindgen]
 pub struct CanvasRenderer {
     context: CanvasRenderingContext2d,
@@ -72,6 +77,7 @@ impl CanvasRenderer {
     }
 
     #[wasm_bindgen]
+    #[allow(clippy::too_many_arguments)]
     pub fn draw_line(&self, x1: f64, y1: f64, x2: f64, y2: f64, color: &str, width: f64, alpha: f64) {
         self.context.save();
         self.context.set_global_alpha(alpha);
@@ -85,7 +91,13 @@ impl CanvasRenderer {
     }
 
     #[wasm_bindgen]
-    pub fn draw_pheromone_trail_simple(&self, cities_json: &str, pheromone_matrix: &[f64], max_pheromone: f64) {
+    pub fn draw_pheromone_trail_simple(&self, cities_json: &str, pheromone_matrix: &[f64], min_pheromone: f64, max_pheromone: f64) {
+        // Trails are drawn relative to the current range; a uniform matrix carries no information
+        let range = max_pheromone - min_pheromone;
+        if range <= 0.0 {
+            return;
+        }
+
         // Parse cities from JSON string
         if let Ok(cities_data) = js_sys::JSON::parse(cities_json) {
             if let Ok(cities_array) = js_sys::Array::from(&cities_data).dyn_into::<js_sys::Array>() {
@@ -94,10 +106,12 @@ impl CanvasRenderer {
                 for i in 0..num_cities {
                     for j in (i + 1)..num_cities {
                         if i * num_cities + j < pheromone_matrix.len() {
-                            let pheromone_level = pheromone_matrix[i * num_cities + j];
+                            // Directional (ATSP) trails are drawn with the stronger of the two directions
+                            let trail = pheromone_matrix[i * num_cities + j].max(pheromone_matrix.get(j * num_cities + i).copied().unwrap_or(0.0));
+                            let pheromone_level = (trail - min_pheromone) / range;
                             if pheromone_level > 0.1 {
-                                let alpha = (pheromone_level / max_pheromone).min(1.0) * 0.8;
-                                let width = (pheromone_level / max_pheromone * 5.0).max(1.0);
+                                let alpha = pheromone_level.min(1.0) * 0.8;
+                                let width = (pheromone_level * 5.0).max(1.0);
                                 
                                 let city1 = cities_array.get(i as u32);
                                 let city2 = cities_array.get(j as u32);
@@ -140,8 +154,8 @@ impl CanvasRenderer {
                     }
                 }
 
-                for i in 1..route.len() {
-                    let city = cities_array.get(route[i]);
+                for &index in &route[1..] {
+                    let city = cities_array.get(index);
                     if !city.is_undefined() {
                         if let (Ok(x), Ok(y)) = (
                             js_sys::Reflect::get(&city, &"x".into()).and_then(|v| v.as_f64().ok_or(JsValue::NULL)),
@@ -159,6 +173,88 @@ impl CanvasRenderer {
         }
     }
 
+    // Draws a giant tour such as [0, 3, 1, 0, 2, 0] as separate vehicle routes through `depot`
+    #[wasm_bindgen]
+    pub fn draw_vehicle_routes(&self, cities_json: &str, route: &[u32], depot: u32, width: f64) {
+        let mut vehicle = 0;
+        let mut start = 0;
+        for end in 1..route.len() {
+            if route[end] == depot {
+                if end - start > 1 {
+                    let color = VEHICLE_COLORS[vehicle % VEHICLE_COLORS.len()];
+                    self.draw_route_simple(cities_json, &route[start..=end], color, width);
+                    vehicle += 1;
+                }
+                start = end;
+            }
+        }
+    }
+
+    // Scatter plot of a Pareto front given as flat x, y cost pairs sorted by x, joined by the
+    // staircase the front dominates; the `selected` point is highlighted
+    #[wasm_bindgen]
+    pub fn draw_pareto_front(&self, points: &[f64], selected: Option<u32>, x_label: &str, y_label: &str) {
+        const MARGIN: f64 = 40.0;
+        self.clear();
+        let (width, height) = (self.width as f64, self.height as f64);
+
+        // Axes
+        self.draw_line(MARGIN, MARGIN / 2.0, MARGIN, height - MARGIN, "#9ca3af", 1.0, 1.0);
+        self.draw_line(MARGIN, height - MARGIN, width - MARGIN / 2.0, height - MARGIN, "#9ca3af", 1.0, 1.0);
+        self.context.set_fill_style_str("#ffffff");
+        self.context.set_font("12px Arial");
+        self.context.set_text_baseline("middle");
+        self.context.set_text_align("center");
+        let _ = self.context.fill_text(x_label, width / 2.0, height - MARGIN / 3.0);
+        self.context.set_text_align("left");
+        let _ = self.context.fill_text(y_label, MARGIN + 6.0, MARGIN / 2.0);
+
+        let pairs: Vec<(f64, f64)> = points.chunks_exact(2).map(|pair| (pair[0], pair[1])).collect();
+        if pairs.is_empty() {
+            return;
+        }
+        let (min_x, max_x) = pairs.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), p| (lo.min(p.0), hi.max(p.0)));
+        let (min_y, max_y) = pairs.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), p| (lo.min(p.1), hi.max(p.1)));
+        // A single point sits in the middle of the plot
+        let scale = |value: f64, min: f64, max: f64| if max > min { (value - min) / (max - min) } else { 0.5 };
+        let to_canvas = |&(x, y): &(f64, f64)| {
+            (
+                MARGIN + 10.0 + scale(x, min_x, max_x) * (width - 2.0 * MARGIN - 20.0),
+                height - MARGIN - 10.0 - scale(y, min_y, max_y) * (height - 2.0 * MARGIN - 20.0),
+            )
+        };
+        let positions: Vec<(f64, f64)> = pairs.iter().map(to_canvas).collect();
+
+        // Range of each criterion at the ends of its axis
+        self.context.set_font("10px Arial");
+        self.context.set_text_align("center");
+        let (left, top) = to_canvas(&(min_x, max_y));
+        let (right, bottom) = to_canvas(&(max_x, min_y));
+        let _ = self.context.fill_text(&format!("{:.1}", min_x), left, height - MARGIN + 12.0);
+        let _ = self.context.fill_text(&format!("{:.1}", max_x), right, height - MARGIN + 12.0);
+        self.context.set_text_align("right");
+        let _ = self.context.fill_text(&format!("{:.1}", max_y), MARGIN - 4.0, top);
+        let _ = self.context.fill_text(&format!("{:.1}", min_y), MARGIN - 4.0, bottom);
+
+        self.context.begin_path();
+        self.context.move_to(positions[0].0, positions[0].1);
+        for pair in positions.windows(2) {
+            self.context.line_to(pair[1].0, pair[0].1);
+            self.context.line_to(pair[1].0, pair[1].1);
+        }
+        self.context.set_stroke_style_str("#4b5563");
+        self.context.set_line_width(1.0);
+        self.context.stroke();
+
+        for (i, &(x, y)) in positions.iter().enumerate() {
+            let is_selected = selected == Some(i as u32);
+            self.context.begin_path();
+            let _ = self.context.arc(x, y, if is_selected { 6.0 } else { 4.0 }, 0.0, 2.0 * std::f64::consts::PI);
+            self.context.set_fill_style_str(if is_selected { "#ef4444" } else { "#22c55e" });
+            self.context.fill();
+        }
+    }
+
     #[wasm_bindgen]
     pub fn draw_ant(&self, x: f64, y: f64, angle: f64) {
         self.context.save();
@@ -191,12 +287,13 @@ impl CanvasRenderer {
     }
 }
 
-#[cfg(test)]
+// Only built for the browser, where wasm_bindgen_test runs
+#[cfg(all(test, target_arch = "wasm32"))]
 mod tests {
-    use super::*;
     use wasm_bindgen_test::*;
 
     #[wasm_bindgen_test]
+    #[allow(clippy::assertions_on_constants)]
     fn test_canvas_renderer_creation() {
         // This test would require a DOM environment
         // For now, we'll just test the struct creation logic